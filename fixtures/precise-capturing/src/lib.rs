@@ -0,0 +1,39 @@
+//! Fixture for return-position impl trait with precise-capturing `use<..>` bounds.
+//!
+//! Expected findings are in `expected.txt`.
+
+// precise capturing in traits is only stable from Rust 1.87, but the nightlies emitting supported
+// rustdoc JSON are older
+#![feature(precise_capturing_in_traits)]
+
+mod private {
+    pub trait Hidden {}
+
+    pub trait AlsoHidden {}
+
+    pub struct Token;
+
+    impl Hidden for Token {}
+
+    impl AlsoHidden for Token {}
+}
+
+/// Not a leak, since it's importable.
+pub trait Visible {}
+
+impl Visible for private::Token {}
+
+/// RPIT whose bounds leak `Hidden`.
+pub fn rpit<'a, T>(_x: &'a T) -> impl private::Hidden + use<'a, T> {
+    private::Token
+}
+
+/// RPIT which leaks nothing.
+pub fn rpit_visible<'a, T>(_x: &'a T) -> impl Visible + use<'a, T> {
+    private::Token
+}
+
+pub trait Factory {
+    /// RPITIT whose bounds leak `AlsoHidden`.
+    fn make<'a>(&'a self) -> impl private::AlsoHidden + use<'a, Self>;
+}
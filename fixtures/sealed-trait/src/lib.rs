@@ -0,0 +1,33 @@
+//! Fixture for the sealed trait pattern. `Sealed` is not reported unless `--show-sealed` is passed,
+//! whereas `Unsealed` isn't a seal, since downstream types can implement it through its blanket impl.
+//! `Bound` would be a seal, but is also visible as a bound of a function, which needs it named.
+//!
+//! Expected findings are in `expected.txt`, and in `show-sealed.txt` with `--show-sealed`.
+
+mod private {
+    pub trait Sealed {}
+
+    impl Sealed for u8 {}
+    impl Sealed for u16 {}
+
+    pub trait Unsealed {}
+
+    impl<T: Clone> Unsealed for T {}
+
+    pub trait Bound {}
+
+    impl Bound for u8 {}
+}
+
+pub trait Small: private::Sealed {}
+
+impl Small for u8 {}
+impl Small for u16 {}
+
+pub trait Cloneable: private::Unsealed {}
+
+pub trait Tiny: private::Bound {}
+
+impl Tiny for u8 {}
+
+pub fn tiny<T: private::Bound>(_: T) {}
@@ -0,0 +1,326 @@
+//! Applying suggested fixes by editing the root crate's source files.
+
+use crate::{
+    error::{Result, eyre, ensure, WrapErr as _},
+    cli_args::CliArgs,
+    item_graph::{GraphCache, CanonId},
+    report::{Finding, Location},
+    fixes::FixKind,
+};
+use std::{
+    collections::BTreeMap,
+    fs,
+    ops::Range,
+    path::{Path, PathBuf},
+    process::Command,
+};
+
+
+// lines of unchanged context around each hunk of a diff
+const DIFF_CONTEXT: usize = 3;
+
+// pending edits to a single source file
+#[derive(Default)]
+struct FileEdit {
+    // original lines of the file, or None if it doesn't exist yet
+    original: Option<Vec<String>>,
+    // line ending the file uses, which is kept when writing it
+    line_ending: &'static str,
+    // lines to insert, keyed by the 0-indexed original line to insert them before
+    insertions: BTreeMap<usize, Vec<String>>,
+}
+
+impl FileEdit {
+    fn lines(&self) -> &[String] {
+        self.original.as_deref().unwrap_or(&[])
+    }
+
+    // insert a line before the given original line, unless the given range of original lines,
+    // which is that of the module it's inserted into, already contains it
+    fn insert(&mut self, before: usize, line: String, module_lines: Range<usize>) {
+        let trimmed = line.trim();
+        let len = self.lines().len();
+        let (start, end) = (module_lines.start.min(len), module_lines.end.min(len));
+        let exists = self.lines()[start..end].iter()
+            .chain(self.insertions.range(start..=end).flat_map(|(_, lines)| lines))
+            .any(|existing| existing.trim() == trimmed);
+        if !exists {
+            self.insertions.entry(before).or_default().push(line);
+        }
+    }
+
+    fn new_lines(&self) -> Vec<String> {
+        let lines = self.lines();
+        let mut new_lines = Vec::new();
+        for i in 0..=lines.len() {
+            if let Some(inserted) = self.insertions.get(&i) {
+                new_lines.extend(inserted.iter().cloned());
+            }
+            if let Some(line) = lines.get(i) {
+                new_lines.push(line.clone());
+            }
+        }
+        new_lines
+    }
+}
+
+/// Insert the preferred `pub use` fix for each finding into the source of the module it should go
+/// in, or print the edits as a unified diff if `--dry-run` is given. Findings are paired with the
+/// graph they were found in.
+///
+/// Statements already present are not inserted again, so this is idempotent.
+pub fn apply_fixes(args: &CliArgs, roots: &[(&GraphCache, &[Finding])]) -> Result<()> {
+    let workspace_root = args.workspace_root()?;
+    let mut edits: BTreeMap<PathBuf, FileEdit> = BTreeMap::new();
+
+    for &(graph, findings) in roots {
+        for finding in findings {
+            let Some(fix) = finding.fixes.first() else { continue };
+            if fix.kind != FixKind::ReexportItem {
+                continue;
+            }
+            if let Some(ref reexport_module) = args.reexport_module {
+                // put everything in a dedicated module next to the crate root
+                let crate_root = finding.primary().witness[0].id;
+                let (root_path, root_line, root_lines) = module_insertion_point(
+                    graph, &workspace_root, &mut edits, crate_root,
+                )?;
+                let module_path = root_path.with_file_name(format!("{}.rs", reexport_module));
+                let declaration = format!("pub mod {};", reexport_module);
+                edits.get_mut(&root_path).unwrap().insert(root_line, declaration, root_lines);
+                let module_edit = file_edit(&mut edits, &module_path)?;
+                let end = module_edit.lines().len();
+                module_edit.insert(end, fix.statement.clone(), 0..end);
+            } else {
+                let (path, line, module_lines) = module_insertion_point(
+                    graph, &workspace_root, &mut edits, fix.module_id,
+                )?;
+                let indent = edits[&path].lines().get(line)
+                    .filter(|_| !is_file_module(graph, fix.module_id))
+                    .map(|closing| format!("{}    ", indentation(closing)))
+                    .unwrap_or_default();
+                let statement = format!("{}{}", indent, fix.statement);
+                edits.get_mut(&path).unwrap().insert(line, statement, module_lines);
+            }
+        }
+    }
+    edits.retain(|_, edit| !edit.insertions.is_empty());
+
+    if args.dry_run {
+        for (path, edit) in &edits {
+            let display_path = path.strip_prefix(&workspace_root).unwrap_or(path);
+            print_diff(display_path, edit);
+        }
+        return Ok(());
+    }
+
+    if !args.force {
+        for path in edits.keys() {
+            match has_uncommitted_changes(path) {
+                Some(changed) => ensure!(
+                    !changed,
+                    "Refusing to edit {} because it has uncommitted changes (pass --force to edit \
+                    it anyways)",
+                    path.display(),
+                ),
+                None => eprintln!(
+                    "warning: unable to check {} for uncommitted changes, since it's not in a git \
+                    repository or git is not installed",
+                    path.display(),
+                ),
+            }
+        }
+    }
+    for (path, edit) in &edits {
+        let mut contents = edit.new_lines().join(edit.line_ending);
+        contents.push_str(edit.line_ending);
+        fs::write(path, contents)
+            .wrap_err_with(|| eyre!("Failed to write {}", path.display()))?;
+        eprintln!(
+            "added {} line(s) to {}",
+            edit.insertions.values().map(Vec::len).sum::<usize>(),
+            path.display(),
+        );
+    }
+    Ok(())
+}
+
+// get the pending edit for the given file, loading it if not yet loaded
+fn file_edit<'e>(
+    edits: &'e mut BTreeMap<PathBuf, FileEdit>,
+    path: &Path,
+) -> Result<&'e mut FileEdit> {
+    if !edits.contains_key(path) {
+        let (original, line_ending) = match fs::read_to_string(path) {
+            Ok(contents) => (
+                Some(contents.lines().map(String::from).collect()),
+                if contents.contains("\r\n") { "\r\n" } else { "\n" },
+            ),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => (None, "\n"),
+            Err(e) => return Err(e).wrap_err_with(|| eyre!("Failed to read {}", path.display())),
+        };
+        edits.insert(path.to_owned(), FileEdit { original, line_ending, ..Default::default() });
+    }
+    Ok(edits.get_mut(path).unwrap())
+}
+
+// determine the file and 0-indexed line before which statements should be inserted to add them to
+// the given module, and the range of lines the module spans in it, loading the file into the
+// pending edits
+fn module_insertion_point(
+    graph: &GraphCache,
+    workspace_root: &Path,
+    edits: &mut BTreeMap<PathBuf, FileEdit>,
+    module_id: CanonId,
+) -> Result<(PathBuf, usize, Range<usize>)> {
+    let item = &graph[module_id];
+    let span = item.span.as_ref()
+        .ok_or_else(|| eyre!("Module {:?} has no source span", item.name))?;
+    let path = workspace_root.join(&span.filename);
+    let edit = file_edit(edits, &path)?;
+    let lines = edit.lines();
+
+    if is_file_module(graph, module_id) {
+        // after the last top-level use statement, or after the leading inner attributes and
+        // module docs if there is none
+        let last_use = lines.iter().rposition(|line| is_use_statement(line));
+        let line = match last_use {
+            Some(i) => i + lines[i..].iter().position(|line| line.trim_end().ends_with(';'))
+                .map(|offset| offset + 1)
+                .unwrap_or(1),
+            None => header_end(lines),
+        };
+        Ok((path, line, 0..lines.len()))
+    } else {
+        // before the inline module's closing brace
+        let location = Location::from(span);
+        let end = location.end_line.saturating_sub(1);
+        let line = lines.iter().enumerate().skip(end)
+            .find(|&(_, line)| line.trim_start().starts_with('}'))
+            .map(|(i, _)| i)
+            .ok_or_else(|| eyre!(
+                "Failed to find end of inline module {:?} in {}", item.name, path.display()
+            ))?;
+        Ok((path, line, location.line.saturating_sub(1)..line))
+    }
+}
+
+// determine the 0-indexed line after the inner attributes, module docs and blank lines a file
+// starts with, skipping attributes and block comments spanning several lines as a whole
+fn header_end(lines: &[String]) -> usize {
+    let mut i = 0;
+    while let Some(line) = lines.get(i) {
+        let line = line.trim_start();
+        let end = if line.starts_with("#![") {
+            // until the brackets are balanced
+            let mut depth = 0i32;
+            lines[i..].iter()
+                .position(|line| {
+                    depth += line.matches('[').count() as i32 - line.matches(']').count() as i32;
+                    depth <= 0
+                })
+        } else if line.starts_with("/*") {
+            lines[i..].iter().position(|line| line.contains("*/"))
+        } else if line.starts_with("//!") || line.is_empty() {
+            Some(0)
+        } else {
+            break;
+        };
+        match end {
+            Some(offset) => i += offset + 1,
+            // unterminated, so there's nothing to insert after
+            None => return lines.len(),
+        }
+    }
+    i
+}
+
+// whether a module item's contents are an entire file, rather than an inline `mod name { }`,
+// which is the case if it's in a different file than the module declaring it
+fn is_file_module(graph: &GraphCache, module_id: CanonId) -> bool {
+    let filename = |id| graph[id].span.as_ref().map(|span| span.filename.clone());
+    match graph.parent_module(module_id) {
+        Some(parent_id) => filename(parent_id) != filename(module_id),
+        None => true, // crate root
+    }
+}
+
+// whether a line starts a top-level use statement of any visibility
+fn is_use_statement(line: &str) -> bool {
+    let mut rest = line;
+    if let Some(after_pub) = rest.strip_prefix("pub") {
+        rest = after_pub.trim_start();
+        if rest.starts_with('(') {
+            let Some(close) = rest.find(')') else { return false };
+            rest = rest[close + 1..].trim_start();
+        }
+    }
+    rest.starts_with("use ")
+}
+
+fn indentation(line: &str) -> &str {
+    &line[..line.len() - line.trim_start().len()]
+}
+
+// whether git considers the given file to differ from what's committed, or None if that can't be
+// determined because it's outside of a git repository or git isn't installed
+fn has_uncommitted_changes(path: &Path) -> Option<bool> {
+    let dir = path.parent().unwrap_or(Path::new("."));
+    let output = Command::new("git")
+        .arg("status")
+        .arg("--porcelain")
+        .arg("--")
+        .arg(path)
+        .current_dir(dir)
+        .output();
+    match output {
+        Ok(output) if output.status.success() => Some(!output.stdout.is_empty()),
+        _ => None,
+    }
+}
+
+// print a unified diff of a file edit, which consists only of insertions
+fn print_diff(display_path: &Path, edit: &FileEdit) {
+    let lines = edit.lines();
+    if edit.original.is_some() {
+        println!("--- a/{}", display_path.display());
+    } else {
+        println!("--- /dev/null");
+    }
+    println!("+++ b/{}", display_path.display());
+
+    // group insertion points which are close enough for their context to overlap into hunks
+    let mut hunks: Vec<Vec<usize>> = Vec::new();
+    for &at in edit.insertions.keys() {
+        match hunks.last_mut() {
+            Some(hunk) if at - hunk.last().unwrap() <= 2 * DIFF_CONTEXT => hunk.push(at),
+            _ => hunks.push(vec![at]),
+        }
+    }
+
+    // track how many lines have been inserted before each hunk, for new file line numbers
+    let mut inserted_before = 0;
+    for hunk in hunks {
+        let start = hunk[0].saturating_sub(DIFF_CONTEXT);
+        let end = (hunk.last().unwrap() + DIFF_CONTEXT).min(lines.len());
+        let inserted = hunk.iter().map(|at| edit.insertions[at].len()).sum::<usize>();
+        let old_len = end - start;
+        let new_len = old_len + inserted;
+        println!(
+            "@@ -{},{} +{},{} @@",
+            if old_len == 0 { start } else { start + 1 }, old_len,
+            start + inserted_before + 1, new_len,
+        );
+        for i in start..=end {
+            if let Some(added) = edit.insertions.get(&i) {
+                for line in added {
+                    println!("+{}", line);
+                }
+            }
+            if let Some(line) = lines.get(i).filter(|_| i < end) {
+                println!(" {}", line);
+            }
+        }
+        inserted_before += inserted;
+    }
+}
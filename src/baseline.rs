@@ -0,0 +1,114 @@
+//! Baseline files recording known findings, so that only new ones are reported.
+
+use crate::{
+    error::{Result, eyre, ensure, OptionExt as _, WrapErr as _},
+    report::Finding,
+};
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    fs,
+    path::Path,
+};
+use serde_json::{Value, json};
+
+
+// version of the baseline file format
+const BASELINE_VERSION: u64 = 1;
+
+/// Set of known findings, keyed on root crate and stable canonical item path.
+#[derive(Debug, Default)]
+pub struct Baseline {
+    // maps root crate name -> keys of its known findings
+    entries: BTreeMap<String, BTreeSet<String>>,
+}
+
+/// Key which identifies a finding across runs. This is based on the leaked item's canonical path,
+/// or the path of its definition if rustdoc doesn't know one, rather than the path through which
+/// it happened to be found, since the latter is fragile.
+pub fn finding_key(finding: &Finding) -> String {
+    finding.item_path()
+}
+
+impl Baseline {
+    pub fn from_findings(roots: &[(String, Vec<Finding>)]) -> Self {
+        let mut baseline = Baseline::default();
+        for &(_, ref findings) in roots {
+            for finding in findings.iter().filter(|finding| !finding.sealed) {
+                baseline.entries.entry(finding.root_crate.clone())
+                    .or_default()
+                    .insert(finding_key(finding));
+            }
+        }
+        baseline
+    }
+
+    pub fn load(path: &Path) -> Result<Self> {
+        let contents = fs::read(path)
+            .wrap_err_with(|| eyre!("Failed to read baseline file {}", path.display()))?;
+        let value = serde_json::from_slice::<Value>(&contents)
+            .wrap_err_with(|| eyre!("Failed to parse baseline file {}", path.display()))?;
+        let version = value.get("version").and_then(Value::as_u64);
+        ensure!(
+            version == Some(BASELINE_VERSION),
+            "Unsupported baseline file version {:?} in {}", version, path.display(),
+        );
+        let mut baseline = Baseline::default();
+        let roots = value.get("findings")
+            .and_then(Value::as_object)
+            .ok_or_eyre("Baseline file missing findings")?;
+        for (root_crate, keys) in roots {
+            let keys = keys.as_array()
+                .ok_or_eyre("Baseline file findings must be arrays")?
+                .iter()
+                .map(|key| key.as_str().map(String::from))
+                .collect::<Option<BTreeSet<String>>>()
+                .ok_or_eyre("Baseline file findings must be strings")?;
+            baseline.entries.insert(root_crate.clone(), keys);
+        }
+        Ok(baseline)
+    }
+
+    pub fn write(&self, path: &Path) -> Result<()> {
+        let document = json!({
+            "version": BASELINE_VERSION,
+            "findings": self.entries,
+        });
+        let mut contents = serde_json::to_string_pretty(&document)?;
+        contents.push('\n');
+        fs::write(path, contents)
+            .wrap_err_with(|| eyre!("Failed to write baseline file {}", path.display()))
+    }
+
+    /// Keep the entries of the given previous baseline for root crates other than the analyzed
+    /// ones, such as those excluded from this run.
+    pub fn keep_unanalyzed(&mut self, previous: Baseline, roots: &[(String, Vec<Finding>)]) {
+        for (root_crate, keys) in previous.entries {
+            let analyzed = roots.iter()
+                .any(|&(ref root_package, _)| root_package.replace('-', "_") == root_crate);
+            if !analyzed {
+                self.entries.insert(root_crate, keys);
+            }
+        }
+    }
+
+    /// Remove findings which are in the baseline, and return the baseline entries of the analyzed
+    /// root crates which no longer correspond to any finding, as `(root crate, key)` pairs.
+    pub fn apply(&self, roots: &mut [(String, Vec<Finding>)]) -> Vec<(String, String)> {
+        let current = Baseline::from_findings(roots);
+        for &mut (_, ref mut findings) in roots.iter_mut() {
+            findings.retain(|finding| !self.entries.get(&finding.root_crate)
+                .is_some_and(|keys| keys.contains(&finding_key(finding))));
+        }
+        let mut fixed = Vec::new();
+        for &(ref root_package, _) in roots.iter() {
+            let root_crate = root_package.replace('-', "_");
+            let Some(keys) = self.entries.get(&root_crate) else { continue };
+            for key in keys {
+                if !current.entries.get(&root_crate).is_some_and(|current| current.contains(key)) {
+                    fixed.push((root_crate.clone(), key.clone()));
+                }
+            }
+        }
+        fixed
+    }
+}
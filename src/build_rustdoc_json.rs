@@ -1,46 +1,229 @@
 
-use crate::error::*;
+use crate::{
+    error::*,
+    format_version::upgrade_format,
+};
 use std::{
-    fs::{File, canonicalize},
-    io::BufReader,
-    env::temp_dir,
+    fs::{self, canonicalize},
+    env::{self, temp_dir},
     path::{self, Path, PathBuf},
+    process::{self, Command},
 };
+use serde::Deserialize;
+use serde_json::Value;
+use rustdoc_types::FORMAT_VERSION;
+
+
+// toolchain rustdoc JSON is built with if none is configured
+const DEFAULT_TOOLCHAIN: &'static str = "nightly";
+
+// names of toolchain files rustup looks for, in order of precedence
+const TOOLCHAIN_FILES: &'static [&'static str] = &["rust-toolchain", "rust-toolchain.toml"];
 
 
+// flags passed to rustdoc in addition to what rustdoc_json passes. hidden items are documented
+// so that whether they count as part of the API is decided by us, rather than by rustdoc stripping
+// them.
+const EXTRA_RUSTDOCFLAGS: &'static str = "-Z unstable-options --document-hidden-items";
+
+/// Arrange for `EXTRA_RUSTDOCFLAGS` to be passed to rustdoc. rustdoc_json::Builder has no way of
+/// passing arbitrary flags, so this is done through the environment, and thus must be called while
+/// the process is still single-threaded.
+pub fn init_rustdoc_flags() {
+    let mut flags = env::var("RUSTDOCFLAGS").unwrap_or_default();
+    if !flags.is_empty() {
+        flags.push(' ');
+    }
+    flags.push_str(EXTRA_RUSTDOCFLAGS);
+    env::set_var("RUSTDOCFLAGS", flags);
+}
+
+/// Flags rustdoc is run with, in addition to the ones selecting the output format, once
+/// `init_rustdoc_flags` has been called.
+pub fn rustdoc_flags() -> String {
+    env::var("RUSTDOCFLAGS").unwrap_or_default()
+}
+
 pub fn build_rustdoc_json(
     manifest_path: impl AsRef<Path>,
     package: &str,
+    toolchain: &str,
 ) -> Result<rustdoc_types::Crate> {
     let package = package.replace("_", "-");
-    build_rustdoc_json_inner(manifest_path.as_ref(), &package)
+    let json_path = build_rustdoc_json_inner(manifest_path.as_ref(), &package, toolchain)
         .or_else(|e| {
             // TODO: utterly disgusting
             let package_underscores = package.replace("-", "_");
             if package != package_underscores {
-                build_rustdoc_json_inner(manifest_path.as_ref(), &package_underscores)
+                build_rustdoc_json_inner(manifest_path.as_ref(), &package_underscores, toolchain)
             } else {
                 Err(e)
             }
-        })
+        })?;
+    // loaded separately so that incompatible output isn't mistaken for the package name being wrong
+    load_rustdoc_json(&json_path)
 }
 
+#[allow(clippy::needless_borrows_for_generic_args)]
 fn build_rustdoc_json_inner(
     manifest_path: impl AsRef<Path>,
     package: &str,
-) -> Result<rustdoc_types::Crate> {
-    let json_path = rustdoc_json::Builder::default()
-        .toolchain("nightly")
+    toolchain: &str,
+) -> Result<PathBuf> {
+    rustdoc_json::Builder::default()
+        .toolchain(toolchain)
         .target_dir(&target_dir(manifest_path.as_ref()))
         .document_private_items(true) // TODO: it is unfortunate we have to do this for now(?)
         .package(package)
         .manifest_path(manifest_path)
         .build()
-        .wrap_err("Failed to build rustdoc JSON")?;
-    let file = File::open(json_path).wrap_err("Failed to open rustdoc JSON file")?;
-    serde_json::from_reader::<_, rustdoc_types::Crate>(BufReader::new(file))
-        .wrap_err("Failed to deserialize rustdoc JSON output")
-    // TODO: assert rustdoc_types::FORMAT_VERSION match
+        .wrap_err("Failed to build rustdoc JSON")
+}
+
+/// Build the rustdoc JSON of several packages, given by package ID specifications, with a single
+/// cargo invocation, which builds them in parallel with the given number of jobs, or cargo's
+/// default. Returns the directory the output files are in, named after each package's library
+/// crate.
+pub fn prebuild_rustdoc_json(
+    manifest_path: &Path,
+    packages: &[String],
+    jobs: Option<usize>,
+    toolchain: &str,
+) -> Result<PathBuf> {
+    let target_dir = target_dir(manifest_path);
+    // the same flags rustdoc_json::Builder passes, but for all packages
+    let mut flags = rustdoc_flags();
+    flags.push_str(" -Z unstable-options --output-format json --document-private-items");
+    let mut command = Command::new("cargo");
+    command
+        .arg(format!("+{}", toolchain))
+        .arg("doc")
+        .arg("--no-deps")
+        .arg("--manifest-path")
+        .arg(manifest_path)
+        .arg("--target-dir")
+        .arg(&target_dir)
+        .env("RUSTDOCFLAGS", flags.trim_start());
+    for package in packages {
+        command.arg("--package").arg(package);
+    }
+    if let Some(jobs) = jobs {
+        command.arg("--jobs").arg(jobs.to_string());
+    }
+    let status = command.status().wrap_err("Failed to run cargo doc")?;
+    ensure!(status.success(), "Failed to build rustdoc JSON of dependencies (see stderr)");
+    Ok(target_dir.join("doc"))
+}
+
+/// Load and deserialize a rustdoc JSON file, upgrading it if it's in an older supported format
+/// version.
+pub fn load_rustdoc_json(json_path: &Path) -> Result<rustdoc_types::Crate> {
+    let bytes = fs::read(json_path)
+        .wrap_err_with(|| eyre!("Failed to read rustdoc JSON file {}", json_path.display()))?;
+    parse_rustdoc_json(&bytes, json_path)
+}
+
+/// Like `load_rustdoc_json`, but for the contents of a file which was already read.
+pub fn parse_rustdoc_json(bytes: &[u8], json_path: &Path) -> Result<rustdoc_types::Crate> {
+    let header = serde_json::from_slice::<FormatVersionHeader>(bytes)
+        .wrap_err_with(|| eyre!("Failed to parse rustdoc JSON file {}", json_path.display()))?;
+    if header.format_version == Some(FORMAT_VERSION) {
+        return serde_json::from_slice::<rustdoc_types::Crate>(bytes).wrap_err_with(|| eyre!(
+            "Failed to deserialize rustdoc JSON file {}", json_path.display(),
+        ));
+    }
+    // only documents which need upgrading go through an untyped representation
+    let mut value = serde_json::from_slice::<Value>(bytes)
+        .wrap_err_with(|| eyre!("Failed to parse rustdoc JSON file {}", json_path.display()))?;
+    upgrade_format(&mut value)
+        .wrap_err_with(|| eyre!("Unsupported rustdoc JSON file {}", json_path.display()))?;
+    serde_json::from_value::<rustdoc_types::Crate>(value)
+        .wrap_err_with(|| eyre!("Failed to deserialize rustdoc JSON file {}", json_path.display()))
+}
+
+// the part of a rustdoc JSON document which says how to deserialize the rest. missing if it's not
+// rustdoc JSON at all, which upgrade_format reports.
+#[derive(Deserialize)]
+struct FormatVersionHeader {
+    format_version: Option<u32>,
+}
+
+/// Get the verbose version string of the rustc of the given toolchain.
+pub fn toolchain_version(toolchain: &str) -> Result<String> {
+    let output = Command::new("rustc")
+        .arg(format!("+{}", toolchain))
+        .arg("--version")
+        .arg("--verbose")
+        .output()?;
+    ensure!(output.status.success(), "Failed to get version of {} toolchain", toolchain);
+    String::from_utf8(output.stdout).wrap_err("Non-UTF-8 rustc version")
+}
+
+/// Determine the toolchain to build rustdoc JSON with if none is given explicitly, like rustup
+/// would for the given directory: `RUSTUP_TOOLCHAIN`, or else the channel of the closest toolchain
+/// file. Since rustdoc JSON requires nightly, either is only used if it's a nightly toolchain, and
+/// unlike rustup, this falls back to nightly rather than the default toolchain.
+pub fn default_toolchain(dir: &Path) -> String {
+    // rustup proxies set RUSTUP_TOOLCHAIN for the programs they run, such as this one under
+    // `cargo run`, so it's often just the toolchain the user happens to build with
+    if let Some(toolchain) = env::var("RUSTUP_TOOLCHAIN").ok().filter(|s| is_nightly(s)) {
+        return toolchain;
+    }
+    let dir = canonicalize(dir).unwrap_or_else(|_| dir.to_owned());
+    dir.ancestors()
+        .flat_map(|dir| TOOLCHAIN_FILES.iter().map(move |&name| dir.join(name)))
+        .find_map(|path| fs::read_to_string(path).ok())
+        .and_then(|contents| toolchain_file_channel(&contents))
+        .filter(|channel| is_nightly(channel))
+        .unwrap_or_else(|| DEFAULT_TOOLCHAIN.to_owned())
+}
+
+// whether a toolchain name, such as `nightly-2024-11-15-x86_64-unknown-linux-gnu`, names a nightly
+fn is_nightly(toolchain: &str) -> bool {
+    toolchain == "nightly" || toolchain.starts_with("nightly-")
+}
+
+// get the channel a toolchain file specifies, which is either a TOML file with a `[toolchain]`
+// table, or in the legacy format, just the channel. only the simple `channel = "..."` form is
+// understood, to avoid depending on a TOML parser.
+fn toolchain_file_channel(contents: &str) -> Option<String> {
+    if !contents.contains("[toolchain]") {
+        let channel = contents.trim();
+        return Some(channel.to_owned()).filter(|channel| !channel.is_empty());
+    }
+    contents.lines()
+        .filter_map(|line| line.split_once('='))
+        .find(|&(key, _)| key.trim() == "channel")
+        .map(|(_, value)| value.trim().trim_matches('"').to_owned())
+}
+
+/// Determine the rustdoc JSON format version the given toolchain emits, by documenting an empty
+/// crate, which is much faster than building actual packages.
+pub fn probe_format_version(toolchain: &str) -> Result<u64> {
+    let dir = temp_dir().join(format!("should-be-public-checker-probe-{}", process::id()));
+    fs::create_dir_all(&dir).wrap_err("Failed to create directory for probing rustdoc")?;
+    let source = dir.join("probe.rs");
+    fs::write(&source, "").wrap_err("Failed to create crate for probing rustdoc")?;
+    let output = Command::new("rustdoc")
+        .arg(format!("+{}", toolchain))
+        .args(["-Z", "unstable-options", "--output-format", "json", "--crate-name", "probe"])
+        .arg("-o")
+        .arg(&dir)
+        .arg(&source)
+        .output()
+        .wrap_err("Failed to run rustdoc")?;
+    let json = fs::read(dir.join("probe.json"));
+    let _ = fs::remove_dir_all(&dir);
+    ensure!(
+        output.status.success(),
+        "Failed to build rustdoc JSON with {} toolchain: {}",
+        toolchain, String::from_utf8_lossy(&output.stderr).trim(),
+    );
+    serde_json::from_slice::<Value>(&json.wrap_err("Failed to read probe rustdoc JSON")?)
+        .wrap_err("Failed to parse probe rustdoc JSON")?
+        .get("format_version")
+        .and_then(Value::as_u64)
+        .ok_or_eyre("Missing format_version in probe rustdoc JSON")
 }
 
 fn target_dir(manifest_path: &Path) -> PathBuf {
@@ -1,15 +1,283 @@
 
 use crate::error::*;
 use std::{
-    path::Path,
+    collections::HashMap,
+    path::{Path, PathBuf},
     process::Command,
 };
 use serde_json::Value;
 
 
-/// Assuming the given manifest path has a default package, get that package name such that running
-/// cargo commands with `--package ${PACKAGE_NAME}` would not change their behavior.
-pub fn default_package_name(manifest_path: impl AsRef<Path>) -> Result<String> {
+/// Assuming the workspace described by the given output of `metadata_no_deps` has a default package,
+/// get that package name such that running cargo commands with `--package ${PACKAGE_NAME}` would
+/// not change their behavior.
+pub fn default_package_name(metadata: &Value) -> Result<String> {
+    metadata
+        .get("packages")
+        .and_then(|value| value.get(0))
+        .and_then(|value| value.get("name"))
+        .and_then(|value| value.as_str())
+        .map(String::from)
+        .ok_or_eyre("Failed to extract package name from output of cargo metadata")
+}
+
+/// Get the names of all workspace members which have a library target, in the order the given
+/// output of `metadata_no_deps` lists them.
+pub fn workspace_library_members(metadata: &Value) -> Result<Vec<String>> {
+    let packages = metadata
+        .get("packages")
+        .and_then(|value| value.as_array())
+        .ok_or_eyre("Failed to extract packages from output of cargo metadata")?;
+    let mut members = Vec::new();
+    for package in packages {
+        let name = package.get("name")
+            .and_then(|value| value.as_str())
+            .ok_or_eyre("Failed to extract package name from output of cargo metadata")?;
+        let has_lib = package.get("targets")
+            .and_then(|value| value.as_array())
+            .ok_or_eyre("Failed to extract package targets from output of cargo metadata")?
+            .iter()
+            .filter_map(|target| target.get("kind").and_then(|value| value.as_array()))
+            .flatten()
+            .any(|kind| matches!(kind.as_str(), Some("lib" | "rlib" | "dylib")));
+        if has_lib {
+            members.push(name.to_owned());
+        }
+    }
+    Ok(members)
+}
+
+/// Get the root directory of the workspace described by the given output of `metadata_no_deps`,
+/// which is the directory paths in rustdoc JSON spans are relative to.
+pub fn workspace_root(metadata: &Value) -> Result<PathBuf> {
+    metadata
+        .get("workspace_root")
+        .and_then(|value| value.as_str())
+        .map(PathBuf::from)
+        .ok_or_eyre("Failed to extract workspace root from output of cargo metadata")
+}
+
+/// A package which is not a member of the workspace and comes from a registry or git repository,
+/// as resolved by cargo metadata.
+#[derive(Debug, Clone)]
+pub struct DependencyPackage {
+    pub name: String,
+    pub version: String,
+    /// Where it comes from, such as a registry or a git repository at some revision.
+    pub source: String,
+    /// Enabled features, sorted.
+    pub features: Vec<String>,
+}
+
+/// Get all non-local packages in the dependency graph described by the given output of
+/// `metadata_with_deps`.
+pub fn dependency_packages(metadata: &Value) -> Result<Vec<DependencyPackage>> {
+    let mut features = metadata.get("resolve")
+        .and_then(|value| value.get("nodes"))
+        .and_then(|value| value.as_array())
+        .ok_or_eyre("Failed to extract resolve nodes from output of cargo metadata")?
+        .iter()
+        .filter_map(|node| {
+            let id = node.get("id").and_then(|value| value.as_str())?;
+            let mut features = node.get("features")
+                .and_then(|value| value.as_array())?
+                .iter()
+                .filter_map(|value| value.as_str().map(String::from))
+                .collect::<Vec<_>>();
+            features.sort();
+            Some((id, features))
+        })
+        .collect::<HashMap<_, _>>();
+    let packages = metadata.get("packages")
+        .and_then(|value| value.as_array())
+        .ok_or_eyre("Failed to extract packages from output of cargo metadata")?;
+    let mut dependencies = Vec::new();
+    for package in packages {
+        if package.get("source").is_none_or(Value::is_null) {
+            // local packages may change without their version changing
+            continue;
+        }
+        let field = |key: &str| package.get(key)
+            .and_then(|value| value.as_str())
+            .ok_or_else(|| eyre!("Failed to extract package {} from output of cargo metadata", key));
+        dependencies.push(DependencyPackage {
+            name: field("name")?.to_owned(),
+            version: field("version")?.to_owned(),
+            source: field("source")?.to_owned(),
+            features: features.remove(field("id")?).unwrap_or_default(),
+        });
+    }
+    Ok(dependencies)
+}
+
+/// A direct dependency of a package, as resolved by cargo metadata.
+#[derive(Debug, Clone)]
+pub struct DirectDependency {
+    pub package: String,
+    pub version: String,
+    /// Name of its library crate, normalized to use underscores, which rustdoc names its output
+    /// and refers to it by.
+    pub lib_name: String,
+    /// Name the dependent crate refers to it by, which differs from `lib_name` if it's renamed.
+    pub extern_name: String,
+}
+
+impl DirectDependency {
+    /// Package ID specification which cargo resolves to exactly this package, even if there are
+    /// several versions of it in the dependency graph.
+    pub fn spec(&self) -> String {
+        format!("{}@{}", self.package, self.version)
+    }
+}
+
+/// Get the direct normal dependencies of the given workspace members, from the given output of
+/// `metadata_with_deps`. Dependencies of several of the packages are only listed once.
+pub fn direct_dependencies(
+    metadata: &Value,
+    packages: &[String],
+) -> Result<Vec<DirectDependency>> {
+    dependencies_of(metadata, |name, _, package| {
+        packages.iter().any(|package| package == name)
+            && package.get("source").is_none_or(Value::is_null)
+    })
+}
+
+/// Get the direct normal dependencies of the given dependencies, from the given output of
+/// `metadata_with_deps`. Dependencies of several of them are only listed once.
+pub fn dependencies_of_dependencies(
+    metadata: &Value,
+    dependencies: &[DirectDependency],
+) -> Result<Vec<DirectDependency>> {
+    dependencies_of(metadata, |name, version, _| {
+        dependencies.iter()
+            .any(|dependency| dependency.package == name && dependency.version == version)
+    })
+}
+
+// get the direct normal dependencies of the packages in cargo metadata output which match the given
+// predicate, which is passed their name, version and metadata
+fn dependencies_of(
+    metadata: &Value,
+    is_dependent: impl Fn(&str, &str, &Value) -> bool,
+) -> Result<Vec<DirectDependency>> {
+    let mut names = HashMap::new();
+    let mut dependent_ids = Vec::new();
+    for package in metadata.get("packages")
+        .and_then(|value| value.as_array())
+        .ok_or_eyre("Failed to extract packages from output of cargo metadata")?
+    {
+        let (Some(id), Some(name), Some(version)) = (
+            package.get("id").and_then(|value| value.as_str()),
+            package.get("name").and_then(|value| value.as_str()),
+            package.get("version").and_then(|value| value.as_str()),
+        ) else {
+            bail!("Failed to extract package id, name and version from output of cargo metadata")
+        };
+        if let Some(lib_name) = lib_name(package) {
+            names.insert(id, (name, version, lib_name));
+        }
+        if is_dependent(name, version, package) {
+            dependent_ids.push(id);
+        }
+    }
+    let mut dependencies: Vec<DirectDependency> = Vec::new();
+    for node in metadata.get("resolve")
+        .and_then(|value| value.get("nodes"))
+        .and_then(|value| value.as_array())
+        .ok_or_eyre("Failed to extract resolve nodes from output of cargo metadata")?
+    {
+        let is_dependent = node.get("id")
+            .and_then(|value| value.as_str())
+            .is_some_and(|id| dependent_ids.contains(&id));
+        if !is_dependent {
+            continue;
+        }
+        for dep in node.get("deps").and_then(|value| value.as_array()).into_iter().flatten() {
+            let is_normal = dep.get("dep_kinds")
+                .and_then(|value| value.as_array())
+                .is_some_and(|kinds| kinds.iter()
+                    .any(|kind| kind.get("kind").is_none_or(Value::is_null)));
+            let names = dep.get("pkg")
+                .and_then(|value| value.as_str())
+                .and_then(|id| names.get(id));
+            let extern_name = dep.get("name").and_then(|value| value.as_str());
+            if let (true, Some(&(name, version, ref lib_name)), Some(extern_name)) =
+                (is_normal, names, extern_name)
+            {
+                if !dependencies.iter()
+                    .any(|dependency| dependency.package == name && dependency.version == version)
+                {
+                    dependencies.push(DirectDependency {
+                        package: name.to_owned(),
+                        version: version.to_owned(),
+                        lib_name: lib_name.clone(),
+                        extern_name: extern_name.replace('-', "_"),
+                    });
+                }
+            }
+        }
+    }
+    Ok(dependencies)
+}
+
+/// Get the names of the library crates of all workspace members which have one, normalized to use
+/// underscores, keyed by package name, from the given output of `metadata_no_deps`.
+pub fn workspace_lib_names(metadata: &Value) -> Result<HashMap<String, String>> {
+    let packages = metadata.get("packages")
+        .and_then(|value| value.as_array())
+        .ok_or_eyre("Failed to extract packages from output of cargo metadata")?;
+    let mut lib_names = HashMap::new();
+    for package in packages {
+        let name = package.get("name")
+            .and_then(|value| value.as_str())
+            .ok_or_eyre("Failed to extract package name from output of cargo metadata")?;
+        if let Some(lib_name) = lib_name(package) {
+            lib_names.insert(name.to_owned(), lib_name);
+        }
+    }
+    Ok(lib_names)
+}
+
+/// Get the `[workspace.metadata.${KEY}]` table of the workspace described by the given output of
+/// `metadata_no_deps`, if it has one, and the `[package.metadata.${KEY}]` tables of all workspace
+/// members which have one.
+pub fn metadata_tables(
+    metadata: &Value,
+    key: &str,
+) -> Result<(Option<Value>, HashMap<String, Value>)> {
+    let workspace = metadata.get("metadata")
+        .and_then(|value| value.get(key))
+        .cloned();
+    let packages = metadata.get("packages")
+        .and_then(|value| value.as_array())
+        .ok_or_eyre("Failed to extract packages from output of cargo metadata")?
+        .iter()
+        .filter_map(|package| {
+            let name = package.get("name").and_then(|value| value.as_str())?;
+            let table = package.get("metadata").and_then(|value| value.get(key))?;
+            Some((name.to_owned(), table.clone()))
+        })
+        .collect();
+    Ok((workspace, packages))
+}
+
+// get the name of a package's library target, normalized to use underscores, if it has one
+fn lib_name(package: &Value) -> Option<String> {
+    package.get("targets")
+        .and_then(|value| value.as_array())
+        .into_iter()
+        .flatten()
+        .find(|target| target.get("kind")
+            .and_then(|value| value.as_array())
+            .is_some_and(|kinds| kinds.iter().any(|kind|
+                matches!(kind.as_str(), Some("lib" | "rlib" | "dylib" | "proc-macro")))))
+        .and_then(|target| target.get("name"))
+        .and_then(|value| value.as_str())
+        .map(|name| name.replace('-', "_"))
+}
+
+/// Run cargo metadata on the given manifest path without dependencies and parse its output.
+pub fn metadata_no_deps(manifest_path: impl AsRef<Path>) -> Result<Value> {
     let output = Command::new("cargo")
         .arg("metadata")
         .arg("--no-deps")
@@ -21,11 +289,20 @@ pub fn default_package_name(manifest_path: impl AsRef<Path>) -> Result<String> {
     ensure!(output.status.success(), "Failed to run cargo metadata");
 
     serde_json::from_slice::<Value>(&output.stdout)
-        .wrap_err("Failed to parse output of cargo metadata")?
-        .get("packages")
-        .and_then(|value| value.get(0))
-        .and_then(|value| value.get("name"))
-        .and_then(|value| value.as_str())
-        .map(String::from)
-        .ok_or_eyre("Failed to extract package name from output of cargo metadata")
+        .wrap_err("Failed to parse output of cargo metadata")
+}
+
+/// Run cargo metadata on the given manifest path with dependencies and parse its output.
+pub fn metadata_with_deps(manifest_path: impl AsRef<Path>) -> Result<Value> {
+    let output = Command::new("cargo")
+        .arg("metadata")
+        .arg("--format-version=1")
+        .arg("--manifest-path")
+        .arg(manifest_path.as_ref())
+        .output()?;
+
+    ensure!(output.status.success(), "Failed to run cargo metadata");
+
+    serde_json::from_slice::<Value>(&output.stdout)
+        .wrap_err("Failed to parse output of cargo metadata")
 }
@@ -1,11 +1,38 @@
 
 use crate::{
     error::*,
-    build_rustdoc_json::build_rustdoc_json,
-    cargo_metadata::default_package_name,
+    build_rustdoc_json::{
+        build_rustdoc_json,
+        load_rustdoc_json,
+        prebuild_rustdoc_json,
+        default_toolchain,
+        probe_format_version,
+    },
+    format_version::check_format_version,
+    cargo_metadata::{
+        default_package_name,
+        workspace_library_members,
+        workspace_root,
+        workspace_lib_names,
+        direct_dependencies,
+        dependencies_of_dependencies,
+        metadata_no_deps,
+        metadata_with_deps,
+        DirectDependency,
+    },
+    report::Format,
+    config::{Config, Configs},
+    disk_cache::DiskCache,
+};
+use std::{
+    collections::HashMap,
+    env,
+    path::{Path, PathBuf},
+    process::Command,
+    sync::OnceLock,
 };
-use std::path::PathBuf;
 use clap::Parser;
+use serde_json::Value;
 
 
 const CARGO_TOML: &'static str = "Cargo.toml";
@@ -14,18 +41,285 @@ const CARGO_TOML: &'static str = "Cargo.toml";
 pub struct CliArgs {
     #[arg(default_value = ".")]
     pub path: PathBuf,
-    #[arg(short, long)]
+    #[arg(short, long, conflicts_with = "workspace")]
     pub package: Option<String>,
+    /// Analyze every library member of the workspace
+    #[arg(long)]
+    pub workspace: bool,
+    /// Exclude a package from `--workspace` analysis (may be repeated)
+    #[arg(long, requires = "workspace")]
+    pub exclude: Vec<String>,
+    /// Format to output findings in [default: human]
+    #[arg(long, value_enum)]
+    pub format: Option<Format>,
+    /// Instead of reporting findings, explain why the item with the given path is visible
+    #[arg(long, value_name = "PATH")]
+    pub explain: Option<String>,
+    /// Insert the suggested `pub use` statements into the analyzed crate's source files
+    #[arg(long)]
+    pub fix: bool,
+    /// With `--fix`, print the edits as a unified diff instead of making them
+    #[arg(long, requires = "fix")]
+    pub dry_run: bool,
+    /// With `--fix`, edit files even if they have uncommitted changes
+    #[arg(long, requires = "fix")]
+    pub force: bool,
+    /// With `--fix`, put all re-exports in a dedicated module with the given name
+    #[arg(long, requires = "fix", value_name = "NAME")]
+    pub reexport_module: Option<String>,
+    /// Report every signature through which an item is visible, rather than at most --max-exposures
+    #[arg(long)]
+    pub all_exposures: bool,
+    /// Maximum number of signatures to report per item
+    #[arg(long, conflicts_with = "all_exposures", value_name = "N", default_value_t = 20)]
+    pub max_exposures: usize,
+    /// Also list sealed traits, which are not importable intentionally, rather than suppressing them
+    #[arg(long)]
+    pub show_sealed: bool,
+    /// Exit with code 1 if there are any findings, or 2 if analysis encountered errors
+    #[arg(long, visible_alias = "check")]
+    pub deny: bool,
+    /// Only report findings which are not recorded in the given baseline file
+    #[arg(long, value_name = "FILE")]
+    pub baseline: Option<PathBuf>,
+    /// Record all current findings in the given baseline file
+    #[arg(long, value_name = "FILE")]
+    pub write_baseline: Option<PathBuf>,
+    /// Use a pre-built rustdoc JSON file for the given crate rather than building it (may be
+    /// repeated). It must be built with `--document-private-items` and `--document-hidden-items`
+    #[arg(long, value_name = "CRATE=PATH", value_parser = parse_rustdoc_json_arg)]
+    pub rustdoc_json: Vec<(String, PathBuf)>,
+    /// Use pre-built rustdoc JSON files named after their crates in the given directory rather
+    /// than building them, for crates which have one. They must be built with
+    /// `--document-private-items` and `--document-hidden-items`
+    #[arg(long, value_name = "DIR")]
+    pub json_dir: Option<PathBuf>,
+    /// Toolchain to build rustdoc JSON with [default: from RUSTUP_TOOLCHAIN or a rust-toolchain
+    /// file if it's a nightly, or else nightly]
+    #[arg(long)]
+    pub toolchain: Option<String>,
+    /// Number of parallel jobs to build the rustdoc JSON of dependencies with [default: cargo's]
+    #[arg(short, long, value_name = "N")]
+    pub jobs: Option<usize>,
+    /// Build the rustdoc JSON of every crate only when it is first referenced, rather than of all
+    /// direct and re-exported dependencies in parallel up front
+    #[arg(long)]
+    pub no_prebuild: bool,
+    /// Directory to persistently cache dependencies' rustdoc JSON and resolution tables in
+    /// [default: a directory in the user's cache directory]
+    #[arg(long, value_name = "DIR", conflicts_with = "no_cache")]
+    pub cache_dir: Option<PathBuf>,
+    /// Don't use the persistent cache
+    #[arg(long)]
+    pub no_cache: bool,
+    /// Count items which are `#[doc(hidden)]` or only importable through hidden modules as
+    /// importable
+    #[arg(long)]
+    pub hidden_importable: bool,
+    // parsed output of cargo metadata without and with dependencies, run when first needed
+    #[arg(skip)]
+    metadata_no_deps: OnceLock<Value>,
+    #[arg(skip)]
+    metadata_with_deps: OnceLock<Value>,
+    // library crate names of workspace members, looked up when first needed
+    #[arg(skip)]
+    lib_names: OnceLock<HashMap<String, String>>,
+    // why the toolchain is unusable, if it is, checked before building anything for the first time
+    #[arg(skip)]
+    toolchain_error: OnceLock<Option<String>>,
 }
 
 impl CliArgs {
     pub fn root_package(&self) -> Result<String> {
         self.package.clone()
             .map(Ok)
-            .unwrap_or_else(|| default_package_name(self.path.join(CARGO_TOML)))
+            .unwrap_or_else(|| default_package_name(self.metadata_no_deps()?))
+    }
+
+    /// Names of all packages which should be analyzed as a root crate.
+    pub fn root_packages(&self) -> Result<Vec<String>> {
+        if !self.workspace {
+            return Ok(vec![self.root_package()?]);
+        }
+        let members = workspace_library_members(self.metadata_no_deps()?)?;
+        for exclude in &self.exclude {
+            ensure!(
+                members.contains(exclude),
+                "Excluded package {:?} is not a library member of the workspace", exclude,
+            );
+        }
+        Ok(members.into_iter()
+            .filter(|member| !self.exclude.contains(member))
+            .collect())
+    }
+
+    pub fn configs(&self) -> Result<Configs> {
+        Configs::load(self.metadata_no_deps()?)
+    }
+
+    pub fn workspace_root(&self) -> Result<PathBuf> {
+        workspace_root(self.metadata_no_deps()?)
+    }
+
+    /// Get the root directory of the git repository containing the workspace, which code hosts
+    /// resolve annotated paths against, or the workspace root if it's not in a git repository.
+    pub fn repository_root(&self) -> Result<PathBuf> {
+        let workspace_root = self.workspace_root()?;
+        // asking git for the workspace's path within the repository, rather than for the
+        // repository's path, keeps the result a prefix of the workspace root even through symlinks
+        let output = Command::new("git")
+            .arg("rev-parse")
+            .arg("--show-prefix")
+            .current_dir(&workspace_root)
+            .output()
+            .ok()
+            .filter(|output| output.status.success());
+        let Some(output) = output else { return Ok(workspace_root) };
+        let prefix = String::from_utf8(output.stdout)?;
+        let depth = Path::new(prefix.trim_end()).components().count();
+        Ok(workspace_root.ancestors().nth(depth).unwrap_or(&workspace_root).to_owned())
+    }
+
+    /// Get the rustdoc JSON of the given package, loading it if a pre-built file was supplied for
+    /// it, or otherwise building it.
+    pub fn rustdoc_json(&self, package: &str) -> Result<rustdoc_types::Crate> {
+        match self.supplied_rustdoc_json(package) {
+            Some(path) => {
+                let rustdoc_json = load_rustdoc_json(&path)?;
+                // rustdoc JSON records whether private items are included, but not hidden ones
+                ensure!(
+                    rustdoc_json.includes_private,
+                    "Rustdoc JSON file {} doesn't include private items, which the analysis relies \
+                    on (build it with --document-private-items and --document-hidden-items)",
+                    path.display(),
+                );
+                Ok(rustdoc_json)
+            }
+            None => {
+                self.check_toolchain()?;
+                build_rustdoc_json(self.path.join(CARGO_TOML), package, &self.toolchain())
+            }
+        }
+    }
+
+    /// Direct dependencies of the given packages.
+    pub fn direct_dependencies(&self, packages: &[String]) -> Result<Vec<DirectDependency>> {
+        direct_dependencies(self.metadata_with_deps()?, packages)
+    }
+
+    /// Direct dependencies of the given dependencies.
+    pub fn dependencies_of_dependencies(
+        &self,
+        dependencies: &[DirectDependency],
+    ) -> Result<Vec<DirectDependency>> {
+        dependencies_of_dependencies(self.metadata_with_deps()?, dependencies)
+    }
+
+    /// Build the rustdoc JSON of the packages with the given package ID specifications in
+    /// parallel, returning the directory the output files are in.
+    pub fn prebuild_rustdoc_json(&self, packages: &[String]) -> Result<PathBuf> {
+        self.check_toolchain()?;
+        prebuild_rustdoc_json(&self.path.join(CARGO_TOML), packages, self.jobs, &self.toolchain())
+    }
+
+    /// Path of the pre-built rustdoc JSON file supplied for the given package, if any.
+    pub fn supplied_rustdoc_json(&self, package: &str) -> Option<PathBuf> {
+        if self.rustdoc_json.is_empty() && self.json_dir.is_none() {
+            return None;
+        }
+        // rustdoc names output files after the library crate, which uses underscores and may be
+        // named differently than its package. dependencies are already referred to by the former.
+        let crate_name = self.lib_names
+            .get_or_init(|| self.metadata_no_deps()
+                .and_then(workspace_lib_names)
+                .unwrap_or_default())
+            .get(package)
+            .cloned()
+            .unwrap_or_else(|| package.replace('-', "_"));
+        let package_name = package.replace('-', "_");
+        self.rustdoc_json.iter()
+            .rev()
+            .find(|&&(ref name, _)| {
+                let name = name.replace('-', "_");
+                name == crate_name || name == package_name
+            })
+            .map(|&(_, ref path)| path.clone())
+            .or_else(|| self.json_dir.as_ref()
+                .map(|dir| dir.join(format!("{}.json", crate_name)))
+                .filter(|path| path.is_file()))
+    }
+
+    /// Open the persistent cache, unless it's disabled.
+    pub fn disk_cache(&self, config: &Config) -> Result<Option<DiskCache>> {
+        if self.no_cache {
+            return Ok(None);
+        }
+        let dir = match self.cache_dir {
+            Some(ref dir) => dir.clone(),
+            None => user_cache_dir()
+                .ok_or_eyre("Unable to determine the user's cache directory (pass --cache-dir)")?
+                .join("should-be-public-checker"),
+        };
+        DiskCache::new(dir, self.metadata_with_deps()?, config, &self.toolchain()).map(Some)
+    }
+
+    // parsed output of cargo metadata without dependencies
+    fn metadata_no_deps(&self) -> Result<&Value> {
+        if let Some(metadata) = self.metadata_no_deps.get() {
+            return Ok(metadata);
+        }
+        let metadata = metadata_no_deps(self.path.join(CARGO_TOML))?;
+        Ok(self.metadata_no_deps.get_or_init(|| metadata))
+    }
+
+    // parsed output of cargo metadata with dependencies
+    fn metadata_with_deps(&self) -> Result<&Value> {
+        if let Some(metadata) = self.metadata_with_deps.get() {
+            return Ok(metadata);
+        }
+        let metadata = metadata_with_deps(self.path.join(CARGO_TOML))?;
+        Ok(self.metadata_with_deps.get_or_init(|| metadata))
+    }
+
+    /// Toolchain to build rustdoc JSON with.
+    pub fn toolchain(&self) -> String {
+        self.toolchain.clone().unwrap_or_else(|| default_toolchain(&self.path))
+    }
+
+    /// Error if the toolchain to build rustdoc JSON with emits an unsupported format, which is
+    /// faster than finding out by building packages. Only probed the first time.
+    pub fn check_toolchain(&self) -> Result<()> {
+        let error = self.toolchain_error.get_or_init(|| {
+            let toolchain = self.toolchain();
+            probe_format_version(&toolchain)
+                .and_then(check_format_version)
+                .wrap_err_with(|| eyre!("Unable to use toolchain {:?}", toolchain))
+                .err()
+                .map(|e| format!("{:#}", e))
+        });
+        match error {
+            &Some(ref e) => Err(eyre!("{}", e)),
+            &None => Ok(()),
+        }
     }
+}
 
-    pub fn build_rustdoc_json(&self, package: &str) -> Result<rustdoc_types::Crate> {
-        build_rustdoc_json(self.path.join(CARGO_TOML), package)
+// the current user's cache directory, like `~/.cache`. cached resolution tables are trusted, so
+// a shared directory like the system's temporary directory would let other users poison them.
+fn user_cache_dir() -> Option<PathBuf> {
+    let var = |name| env::var_os(name).filter(|value| !value.is_empty()).map(PathBuf::from);
+    if cfg!(windows) {
+        var("LOCALAPPDATA")
+    } else if cfg!(target_os = "macos") {
+        var("HOME").map(|home| home.join("Library").join("Caches"))
+    } else {
+        var("XDG_CACHE_HOME").or_else(|| var("HOME").map(|home| home.join(".cache")))
     }
 }
+
+// parse a `--rustdoc-json` argument
+fn parse_rustdoc_json_arg(arg: &str) -> std::result::Result<(String, PathBuf), String> {
+    arg.split_once('=')
+        .map(|(crate_name, path)| (crate_name.to_owned(), PathBuf::from(path)))
+        .ok_or_else(|| format!("expected CRATE=PATH, got {:?}", arg))
+}
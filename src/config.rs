@@ -0,0 +1,230 @@
+//! Configuration read from `[package.metadata.should-be-public]` and
+//! `[workspace.metadata.should-be-public]` tables.
+
+use crate::{
+    error::{Result, eyre, bail, WrapErr as _},
+    cargo_metadata::metadata_tables,
+    report::Format,
+};
+use std::collections::HashMap;
+use clap::ValueEnum;
+use serde_json::{Value, Map};
+
+
+// key of the metadata tables configuration is read from
+const METADATA_KEY: &'static str = "should-be-public";
+
+// crate name aliases which apply even without configuration
+const DEFAULT_CRATE_ALIASES: &'static [(&'static str, &'static str)] = &[
+    ("webpki", "rustls_webpki"),
+];
+
+/// Merged configuration.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Config {
+    /// Crates which are not analyzed, as if they were the standard library.
+    pub ignored_crates: Vec<String>,
+    /// Glob patterns of item paths which are never reported.
+    pub ignored_items: Vec<String>,
+    /// Maps crate name as referenced -> name of package to build rustdoc JSON for.
+    pub crate_aliases: HashMap<String, String>,
+    /// Module names which are not looked into when resolving paths.
+    pub opaque_modules: Vec<String>,
+    /// Default output format.
+    pub format: Option<Format>,
+    /// Whether `#[doc(hidden)]` items count as importable.
+    pub hidden_importable: bool,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Config {
+            ignored_crates: Vec::new(),
+            ignored_items: Vec::new(),
+            crate_aliases: DEFAULT_CRATE_ALIASES.iter()
+                .map(|&(from, to)| (from.to_owned(), to.to_owned()))
+                .collect(),
+            opaque_modules: Vec::new(),
+            format: None,
+            hidden_importable: false,
+        }
+    }
+}
+
+/// All configuration tables of a workspace.
+#[derive(Debug, Default)]
+pub struct Configs {
+    workspace: Option<ConfigTable>,
+    // maps package name -> configuration table
+    packages: HashMap<String, ConfigTable>,
+}
+
+// the contents of a single configuration table
+#[derive(Debug, Default, Clone)]
+struct ConfigTable {
+    ignored_crates: Vec<String>,
+    ignored_items: Vec<String>,
+    crate_aliases: HashMap<String, String>,
+    opaque_modules: Vec<String>,
+    format: Option<Format>,
+    hidden_importable: Option<bool>,
+}
+
+impl Configs {
+    /// Load the configuration from the given output of `metadata_no_deps`.
+    pub fn load(metadata: &Value) -> Result<Self> {
+        let (workspace, packages) = metadata_tables(metadata, METADATA_KEY)?;
+        Ok(Configs {
+            workspace: workspace
+                .map(|table| ConfigTable::parse(&table)
+                    .wrap_err("Invalid [workspace.metadata.should-be-public] table"))
+                .transpose()?,
+            packages: packages.into_iter()
+                .map(|(package, table)| {
+                    let config = ConfigTable::parse(&table).wrap_err_with(|| eyre!(
+                        "Invalid [package.metadata.should-be-public] table in package {:?}",
+                        package,
+                    ))?;
+                    Ok((package, config))
+                })
+                .collect::<Result<_>>()?,
+        })
+    }
+
+    /// Configuration for analyzing the given root packages together, with package tables taking
+    /// precedence over the workspace table.
+    pub fn for_packages<'p>(&self, packages: impl IntoIterator<Item=&'p String>) -> Config {
+        let mut config = Config::default();
+        if let Some(ref workspace) = self.workspace {
+            config.merge(workspace);
+        }
+        for package in packages {
+            if let Some(table) = self.packages.get(package) {
+                config.merge(table);
+            }
+        }
+        config
+    }
+}
+
+impl Config {
+    fn merge(&mut self, table: &ConfigTable) {
+        self.ignored_crates.extend(table.ignored_crates.iter().cloned());
+        self.ignored_items.extend(table.ignored_items.iter().cloned());
+        self.crate_aliases.extend(table.crate_aliases.clone());
+        self.opaque_modules.extend(table.opaque_modules.iter().cloned());
+        self.format = table.format.or(self.format);
+        self.hidden_importable = table.hidden_importable.unwrap_or(self.hidden_importable);
+    }
+
+    /// Whether a finding with any of the given paths should not be reported.
+    pub fn is_ignored_item<'p>(&self, mut paths: impl Iterator<Item=&'p str>) -> bool {
+        paths.any(|path| self.ignored_items.iter().any(|pattern| glob_match(pattern, path)))
+    }
+}
+
+impl ConfigTable {
+    fn parse(table: &Value) -> Result<Self> {
+        let Value::Object(table) = table else { bail!("Expected a table") };
+        let mut config = ConfigTable::default();
+        for (key, value) in table {
+            match key.as_str() {
+                "ignored-crates" => config.ignored_crates = string_array(key, value)?
+                    .into_iter()
+                    .map(|name| name.replace('-', "_"))
+                    .collect(),
+                "ignored-items" => config.ignored_items = string_array(key, value)?,
+                "crate-aliases" => config.crate_aliases = string_table(key, value)?,
+                "opaque-modules" => config.opaque_modules = string_array(key, value)?,
+                "format" => {
+                    let format = value.as_str()
+                        .ok_or_else(|| eyre!("Expected {:?} to be a string", key))?;
+                    config.format = Some(Format::from_str(format, false)
+                        .map_err(|e| eyre!("Invalid format {:?}: {}", format, e))?);
+                }
+                "hidden-importable" => config.hidden_importable = Some(value.as_bool()
+                    .ok_or_else(|| eyre!("Expected {:?} to be a boolean", key))?),
+                _ => bail!("Unknown key {:?}", key),
+            }
+        }
+        Ok(config)
+    }
+}
+
+fn string_array(key: &str, value: &Value) -> Result<Vec<String>> {
+    value.as_array()
+        .and_then(|array| array.iter()
+            .map(|value| value.as_str().map(String::from))
+            .collect::<Option<Vec<String>>>())
+        .ok_or_else(|| eyre!("Expected {:?} to be an array of strings", key))
+}
+
+fn string_table(key: &str, value: &Value) -> Result<HashMap<String, String>> {
+    value.as_object()
+        .and_then(|table: &Map<String, Value>| table.iter()
+            .map(|(k, v)| v.as_str().map(|v| (k.clone(), v.to_owned())))
+            .collect::<Option<HashMap<String, String>>>())
+        .ok_or_else(|| eyre!("Expected {:?} to be a table of strings", key))
+}
+
+/// Match a path against a glob pattern, in which `*` matches any sequence of characters (including
+/// `::`) and `?` matches any single character.
+pub fn glob_match(pattern: &str, text: &str) -> bool {
+    let pattern = pattern.chars().collect::<Vec<_>>();
+    let text = text.chars().collect::<Vec<_>>();
+    // classic greedy matching with backtracking to the most recent star
+    let (mut p, mut t) = (0, 0);
+    let mut star: Option<(usize, usize)> = None;
+    while t < text.len() {
+        if p < pattern.len() && (pattern[p] == '?' || pattern[p] == text[t]) {
+            p += 1;
+            t += 1;
+        } else if p < pattern.len() && pattern[p] == '*' {
+            star = Some((p, t));
+            p += 1;
+        } else if let Some((star_p, star_t)) = star {
+            p = star_p + 1;
+            t = star_t + 1;
+            star = Some((star_p, star_t + 1));
+        } else {
+            return false;
+        }
+    }
+    pattern[p..].iter().all(|&c| c == '*')
+}
+
+#[cfg(test)]
+mod tests {
+    use super::glob_match;
+
+    #[test]
+    fn glob_match_literal() {
+        assert!(glob_match("foo::Bar", "foo::Bar"));
+        assert!(!glob_match("foo::Bar", "foo::Baz"));
+        assert!(!glob_match("foo::Bar", "foo::Bar::Baz"));
+        assert!(!glob_match("foo::Bar::Baz", "foo::Bar"));
+    }
+
+    #[test]
+    fn glob_match_star() {
+        assert!(glob_match("*", ""));
+        assert!(glob_match("*", "foo::Bar"));
+        assert!(glob_match("foo::*", "foo::Bar"));
+        assert!(glob_match("foo::*", "foo::bar::Baz"));
+        assert!(!glob_match("foo::*", "foobar::Baz"));
+        assert!(glob_match("*::Sealed", "foo::private::Sealed"));
+        assert!(!glob_match("*::Sealed", "foo::Sealed2"));
+        assert!(glob_match("quinn_proto::*::Sealed", "quinn_proto::a::b::Sealed"));
+        assert!(glob_match("a*b*c", "aXXbYYbZc"));
+        assert!(!glob_match("a*b*c", "aXXbYYbZ"));
+        assert!(glob_match("foo**", "foo"));
+    }
+
+    #[test]
+    fn glob_match_question_mark() {
+        assert!(glob_match("foo::Ba?", "foo::Bar"));
+        assert!(!glob_match("foo::Ba?", "foo::Ba"));
+        assert!(glob_match("?*", "x"));
+        assert!(!glob_match("?*", ""));
+    }
+}
@@ -0,0 +1,236 @@
+//! Persistent on-disk cache of dependencies' rustdoc JSON and the path resolution tables computed
+//! from it, so that unchanged dependencies are neither rebuilt nor re-resolved across runs.
+
+use crate::{
+    error::*,
+    build_rustdoc_json::{parse_rustdoc_json, rustdoc_flags, toolchain_version},
+    cargo_metadata::dependency_packages,
+    config::Config,
+};
+use std::{
+    collections::HashMap,
+    fs::{self, File},
+    io::{BufWriter, Write as _},
+    path::{Path, PathBuf},
+};
+use rustdoc_types::{Crate, Id, FORMAT_VERSION};
+use serde_json::{Value, Map, json};
+
+
+// bumped whenever the format of cache files changes
+const CACHE_VERSION: u64 = 2;
+
+/// An item as referred to by a persisted table: the name of its crate, normalized to use
+/// underscores, and its id within that crate's rustdoc JSON.
+pub type PersistedId = (String, Id);
+
+/// Resolution tables of a crate, as persisted.
+#[derive(Debug, Default, Clone)]
+pub struct PersistedTables {
+    /// Maps id within the crate -> its canonicalized referent, or `None` if it is ignored.
+    pub resolved: HashMap<Id, Option<PersistedId>>,
+    /// Maps id of a module within the crate -> names importable from it -> their canonicalized
+    /// referents.
+    pub namespaces: HashMap<Id, HashMap<String, PersistedId>>,
+    /// Maps name of each crate the ids are of, including this one -> hash of the rustdoc JSON of
+    /// it they are ids in, and only valid for.
+    pub crates: HashMap<String, u64>,
+}
+
+/// Directory of cached crates, and the keys of all crates which can be cached.
+pub struct DiskCache {
+    dir: PathBuf,
+    // maps crate name, normalized to use underscores -> file stem of its cache files, for crates
+    // which can be cached. crates with several versions in the dependency graph can't be.
+    stems: HashMap<String, Option<String>>,
+    // fingerprint of the configuration resolution depends on
+    config_fingerprint: String,
+}
+
+impl DiskCache {
+    /// Prepare to cache the dependencies of the workspace described by the given output of
+    /// `metadata_with_deps`, as built with the given toolchain, in the given directory.
+    pub fn new(
+        dir: PathBuf,
+        metadata: &Value,
+        config: &Config,
+        toolchain: &str,
+    ) -> Result<Self> {
+        fs::create_dir_all(&dir)
+            .wrap_err_with(|| eyre!("Failed to create cache directory {}", dir.display()))?;
+        let toolchain = toolchain_version(toolchain)?;
+        let rustdoc_flags = rustdoc_flags();
+        let mut stems: HashMap<String, Option<String>> = HashMap::new();
+        for package in dependency_packages(metadata)? {
+            let crate_key = package.name.replace('-', "_");
+            let key = format!(
+                "{}\n{}\n{}\n{}\n{}\n{}\n{}",
+                package.name, package.version, package.source, package.features.join(","),
+                toolchain, rustdoc_flags, FORMAT_VERSION,
+            );
+            let stem = format!("{}-{}-{:016x}", crate_key, package.version, fnv1a(key.as_bytes()));
+            stems.entry(crate_key)
+                .and_modify(|existing| *existing = None)
+                .or_insert(Some(stem));
+        }
+        Ok(DiskCache { dir, stems, config_fingerprint: config_fingerprint(config) })
+    }
+
+    /// Whether the crate with the given name can be cached.
+    pub fn is_cacheable(&self, crate_key: &str) -> bool {
+        self.stem(crate_key).is_some()
+    }
+
+    fn stem(&self, crate_key: &str) -> Option<&str> {
+        self.stems.get(crate_key).and_then(Option::as_deref)
+    }
+
+    fn path(&self, stem: &str, extension: &str) -> PathBuf {
+        self.dir.join(format!("{}.{}", stem, extension))
+    }
+
+    /// Whether there is cached rustdoc JSON of the given crate.
+    pub fn has_rustdoc_json(&self, crate_key: &str) -> bool {
+        self.stem(crate_key).is_some_and(|stem| self.path(stem, "rustdoc.json").is_file())
+    }
+
+    /// Load the cached rustdoc JSON of the given crate, if there is any, along with its hash.
+    pub fn load_rustdoc_json(&self, crate_key: &str) -> Option<(Crate, u64)> {
+        let path = self.path(self.stem(crate_key)?, "rustdoc.json");
+        if !path.is_file() {
+            return None;
+        }
+        fs::read(&path)
+            .wrap_err_with(|| eyre!("Failed to read cache file {}", path.display()))
+            .and_then(|bytes| Ok((parse_rustdoc_json(&bytes, &path)?, fnv1a(&bytes))))
+            .map_err(|e| eprintln!("Ignoring unreadable cache file: {:?}", e))
+            .ok()
+    }
+
+    /// Cache the rustdoc JSON of the given crate, if it can be cached, returning its hash.
+    pub fn store_rustdoc_json(&self, crate_key: &str, rustdoc_json: &Crate) -> Result<Option<u64>> {
+        let Some(stem) = self.stem(crate_key) else { return Ok(None) };
+        let bytes = serde_json::to_vec(rustdoc_json)?;
+        write_atomic(&self.path(stem, "rustdoc.json"), |writer| {
+            writer.write_all(&bytes).map_err(Into::into)
+        })?;
+        Ok(Some(fnv1a(&bytes)))
+    }
+
+    /// Load the cached resolution tables of the given crate, if there are any for the current
+    /// configuration. Their ids are only valid for the rustdoc JSON whose hashes they list.
+    pub fn load_tables(&self, crate_key: &str) -> Option<PersistedTables> {
+        let path = self.path(self.stem(crate_key)?, "tables.json");
+        let value = serde_json::from_slice::<Value>(&fs::read(path).ok()?).ok()?;
+        if value.get("version")?.as_u64()? != CACHE_VERSION
+            || value.get("config")?.as_str()? != self.config_fingerprint
+        {
+            return None;
+        }
+        let mut tables = PersistedTables::default();
+        for (crate_key, hash) in value.get("crates")?.as_object()? {
+            let hash = u64::from_str_radix(hash.as_str()?, 16).ok()?;
+            tables.crates.insert(crate_key.clone(), hash);
+        }
+        for (id, referent) in value.get("resolved")?.as_object()? {
+            let referent = match referent {
+                &Value::Null => None,
+                referent => Some(persisted_id_from_json(referent)?),
+            };
+            tables.resolved.insert(Id(id.parse().ok()?), referent);
+        }
+        for (id, namespace) in value.get("namespaces")?.as_object()? {
+            let namespace = namespace.as_object()?.iter()
+                .map(|(name, referent)| Some((name.clone(), persisted_id_from_json(referent)?)))
+                .collect::<Option<HashMap<_, _>>>()?;
+            tables.namespaces.insert(Id(id.parse().ok()?), namespace);
+        }
+        Some(tables)
+    }
+
+    /// Cache the resolution tables of the given crate, if it can be cached and the hashes of the
+    /// rustdoc JSON of it and all crates they refer to are known.
+    pub fn store_tables(&self, crate_key: &str, tables: &PersistedTables) -> Result<()> {
+        let Some(stem) = self.stem(crate_key) else { return Ok(()) };
+        let referent_crates = tables.resolved.values().flatten()
+            .chain(tables.namespaces.values().flat_map(HashMap::values))
+            .map(|&(ref referent_crate, _)| referent_crate.as_str());
+        let mut crates = Map::new();
+        for referent_crate in [crate_key].into_iter().chain(referent_crates) {
+            let Some(&hash) = tables.crates.get(referent_crate) else { return Ok(()) };
+            crates.insert(referent_crate.to_owned(), format!("{:016x}", hash).into());
+        }
+        let resolved = tables.resolved.iter()
+            .map(|(id, referent)| (
+                id.0.to_string(),
+                referent.as_ref().map(persisted_id_to_json).unwrap_or(Value::Null),
+            ))
+            .collect::<Map<_, _>>();
+        let namespaces = tables.namespaces.iter()
+            .map(|(id, namespace)| (
+                id.0.to_string(),
+                namespace.iter()
+                    .map(|(name, referent)| (name.clone(), persisted_id_to_json(referent)))
+                    .collect::<Map<_, _>>()
+                    .into(),
+            ))
+            .collect::<Map<_, _>>();
+        let document = json!({
+            "version": CACHE_VERSION,
+            "config": self.config_fingerprint,
+            "crates": crates,
+            "resolved": resolved,
+            "namespaces": namespaces,
+        });
+        write_atomic(&self.path(stem, "tables.json"), |writer| {
+            serde_json::to_writer(writer, &document).map_err(Into::into)
+        })
+    }
+}
+
+fn persisted_id_to_json(&(ref crate_key, id): &PersistedId) -> Value {
+    json!([crate_key, id.0])
+}
+
+fn persisted_id_from_json(value: &Value) -> Option<PersistedId> {
+    let crate_key = value.get(0)?.as_str()?;
+    let id = value.get(1)?.as_u64()?;
+    Some((crate_key.to_owned(), Id(id.try_into().ok()?)))
+}
+
+// write a file by writing a temporary file and renaming it into place, so that concurrent runs
+// never see partially written files
+fn write_atomic(
+    path: &Path,
+    write: impl FnOnce(&mut BufWriter<File>) -> Result<()>,
+) -> Result<()> {
+    let tmp_path = path.with_extension(format!("tmp{}", std::process::id()));
+    let mut writer = BufWriter::new(File::create(&tmp_path)
+        .wrap_err_with(|| eyre!("Failed to create cache file {}", tmp_path.display()))?);
+    write(&mut writer)?;
+    writer.into_inner().map_err(|e| e.into_error())?;
+    fs::rename(&tmp_path, path)
+        .wrap_err_with(|| eyre!("Failed to write cache file {}", path.display()))
+}
+
+// fingerprint of the parts of the configuration which path resolution depends on
+fn config_fingerprint(config: &Config) -> String {
+    let mut ignored_crates = config.ignored_crates.clone();
+    ignored_crates.sort();
+    let mut crate_aliases = config.crate_aliases.iter().collect::<Vec<_>>();
+    crate_aliases.sort();
+    let mut opaque_modules = config.opaque_modules.clone();
+    opaque_modules.sort();
+    let key = format!("{:?}\n{:?}\n{:?}", ignored_crates, crate_aliases, opaque_modules);
+    format!("{:016x}", fnv1a(key.as_bytes()))
+}
+
+// 64-bit FNV-1a hash, which unlike std's hashers is stable across releases
+fn fnv1a(bytes: &[u8]) -> u64 {
+    let mut hash: u64 = 0xcbf29ce484222325;
+    for &byte in bytes {
+        hash ^= byte as u64;
+        hash = hash.wrapping_mul(0x100000001b3);
+    }
+    hash
+}
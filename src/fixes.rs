@@ -0,0 +1,149 @@
+//! Suggesting `pub use` and `pub extern crate` statements which would fix findings.
+
+use crate::{
+    error::{Result, OptionExt as _},
+    item_graph::{GraphCache, CanonId, BfsNode, ResolveErr, Hop},
+};
+use std::collections::HashMap;
+use rustdoc_types::{ItemEnum, Visibility};
+
+
+/// A statement which could be added to the root crate to make a leaked item importable.
+#[derive(Debug, Clone)]
+pub struct Fix {
+    pub kind: FixKind,
+    /// The module in the root crate the statement should be added to.
+    pub module_id: CanonId,
+    /// Display path of that module.
+    pub module: String,
+    /// The statement, ready to paste.
+    pub statement: String,
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum FixKind {
+    /// `pub use` of the leaked item itself.
+    ReexportItem,
+    /// `pub extern crate` of the leaked item's defining crate.
+    ReexportCrate,
+}
+
+impl FixKind {
+    pub fn name(self) -> &'static str {
+        match self {
+            FixKind::ReexportItem => "reexport_item",
+            FixKind::ReexportCrate => "reexport_crate",
+        }
+    }
+}
+
+/// Computes suggested fixes for findings, caching the dependencies of each root package.
+#[derive(Default)]
+pub struct FixSuggester {
+    // maps root package name -> library crate names of its direct dependencies -> names it refers
+    // to them by
+    dependencies: HashMap<String, HashMap<String, String>>,
+}
+
+impl FixSuggester {
+    /// Suggest fixes for the leaked item with the given witness chain, in order of preference.
+    ///
+    /// Returns no fixes if the item cannot be named from outside its defining crate at all.
+    pub fn suggest(
+        &mut self,
+        graph: &mut GraphCache,
+        root_package: &str,
+        importable: &HashMap<CanonId, BfsNode>,
+        witness: &[Hop],
+    ) -> Result<Vec<Fix>> {
+        let root_crate = root_package.replace('-', "_");
+        let id = witness.last().ok_or_eyre("Empty witness chain")?.id;
+        let crate_name = graph.crate_name(id).to_owned();
+        let (module_id, module) = target_module(graph, &root_crate, witness);
+
+        if crate_name == root_crate {
+            // any item in the root crate can be re-exported through its definition path, as long
+            // as it's not restricted to the crate
+            if graph[id].visibility != Visibility::Public {
+                return Ok(Vec::new());
+            }
+            let Some(summary) = graph.item_summary(id) else { return Ok(Vec::new()) };
+            let path = summary.path[1..].join("::");
+            return Ok(vec![Fix {
+                kind: FixKind::ReexportItem,
+                module_id,
+                module,
+                statement: format!("pub use crate::{};", path),
+            }]);
+        }
+
+        let crate_root = match graph.resolve_crate(&crate_name) {
+            Ok(crate_root) => crate_root.0,
+            Err(ResolveErr::Fail(e)) => return Err(e),
+            Err(ResolveErr::Ignore) => return Ok(Vec::new()),
+        };
+        let Some(item_path) = graph.crate_public_items(id)?.get(&id).map(|node| node.path.clone())
+            else { return Ok(Vec::new()) };
+
+        let mut fixes = Vec::new();
+        if let Some(crate_root_node) = importable.get(&crate_root) {
+            // the defining crate is already importable from the root crate, so re-export the item
+            // through that
+            let via = replace_first_segment(&crate_root_node.path, &root_crate, "crate");
+            let item_path = replace_first_segment(&item_path, &crate_name, &via);
+            fixes.push(Fix {
+                kind: FixKind::ReexportItem,
+                module_id,
+                module,
+                statement: format!("pub use {};", item_path),
+            });
+        } else {
+            // otherwise, the root crate can only name the defining crate if it's a direct
+            // dependency, possibly renamed
+            if !self.dependencies.contains_key(root_package) {
+                let dependencies = graph.cli_args.direct_dependencies(&[root_package.to_owned()])?
+                    .into_iter()
+                    .map(|dependency| (dependency.lib_name, dependency.extern_name))
+                    .collect();
+                self.dependencies.insert(root_package.to_owned(), dependencies);
+            }
+            let Some(extern_name) = self.dependencies[root_package].get(&crate_name)
+                else { return Ok(fixes) };
+            let item_path = replace_first_segment(&item_path, &crate_name, extern_name);
+            fixes.push(Fix {
+                kind: FixKind::ReexportItem,
+                module_id,
+                module,
+                statement: format!("pub use {};", item_path),
+            });
+            fixes.push(Fix {
+                kind: FixKind::ReexportCrate,
+                module_id: witness[0].id,
+                module: witness[0].path.clone(),
+                statement: format!("pub extern crate {};", extern_name),
+            });
+        }
+        Ok(fixes)
+    }
+}
+
+// the module of the root crate closest to the leaked item on its witness chain
+fn target_module(graph: &GraphCache, root_crate: &str, witness: &[Hop]) -> (CanonId, String) {
+    let hop = witness.iter()
+        .rev()
+        .find(|hop|
+            matches!(&graph[hop.id].inner, &ItemEnum::Module(_))
+            && graph.crate_name(hop.id) == root_crate)
+        // the witness chain always starts at the root crate's root module
+        .unwrap_or(&witness[0]);
+    (hop.id, hop.path.clone())
+}
+
+// replace the given first segment of a display path
+fn replace_first_segment(path: &str, first: &str, replacement: &str) -> String {
+    match path.strip_prefix(first) {
+        Some(rest) if rest.is_empty() || rest.starts_with("::") =>
+            format!("{}{}", replacement, rest),
+        _ => path.to_owned(),
+    }
+}
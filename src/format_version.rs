@@ -0,0 +1,60 @@
+//! Support for rustdoc JSON format versions other than the one `rustdoc_types` describes, by
+//! upgrading documents at the JSON level before deserializing them.
+
+use crate::error::*;
+use serde_json::Value;
+use rustdoc_types::FORMAT_VERSION;
+
+
+// oldest format version which can be upgraded to FORMAT_VERSION
+const OLDEST_FORMAT_VERSION: u32 = 35;
+
+// rough range of nightly toolchains which emit supported format versions, for error messages
+const SUPPORTED_NIGHTLIES: &'static str = "nightlies from around October to November 2024";
+
+/// Check the format version of a rustdoc JSON document, and upgrade it to `FORMAT_VERSION` if it's
+/// older but supported. Errors if it's not supported.
+pub fn upgrade_format(value: &mut Value) -> Result<()> {
+    let format_version = value.get("format_version")
+        .and_then(Value::as_u64)
+        .ok_or_eyre("Missing format_version, this doesn't look like rustdoc JSON")?;
+    check_format_version(format_version)?;
+    if format_version < 36 {
+        upgrade_35_to_36(value);
+    }
+    value["format_version"] = FORMAT_VERSION.into();
+    Ok(())
+}
+
+/// Error if the given format version is not supported.
+pub fn check_format_version(format_version: u64) -> Result<()> {
+    ensure!(
+        (OLDEST_FORMAT_VERSION as u64..=FORMAT_VERSION as u64).contains(&format_version),
+        "Rustdoc JSON has format version {}, but only versions {} to {} are supported, which are \
+        emitted by {}. {}",
+        format_version,
+        OLDEST_FORMAT_VERSION,
+        FORMAT_VERSION,
+        SUPPORTED_NIGHTLIES,
+        if format_version > FORMAT_VERSION as u64 {
+            "Use an older nightly toolchain, for example with --toolchain."
+        } else {
+            "Use a newer nightly toolchain, for example with --toolchain."
+        },
+    );
+    Ok(())
+}
+
+// 36 renamed Trait::is_object_safe to is_dyn_compatible
+fn upgrade_35_to_36(value: &mut Value) {
+    let Some(index) = value.get_mut("index").and_then(Value::as_object_mut) else { return };
+    for item in index.values_mut() {
+        let Some(trait_) = item.get_mut("inner")
+            .and_then(|inner| inner.get_mut("trait"))
+            .and_then(Value::as_object_mut)
+            else { continue };
+        if let Some(is_object_safe) = trait_.remove("is_object_safe") {
+            trait_.insert("is_dyn_compatible".to_owned(), is_object_safe);
+        }
+    }
+}
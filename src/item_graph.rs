@@ -1,6 +1,10 @@
 
 use crate::{
     cli_args::CliArgs,
+    cargo_metadata::DirectDependency,
+    config::Config,
+    disk_cache::{DiskCache, PersistedTables, PersistedId},
+    build_rustdoc_json::load_rustdoc_json,
     error::{
         Error,
         eyre,
@@ -9,13 +13,16 @@ use crate::{
         WrapErr as _,
     },
     pretty_print::*,
+    link_importable,
 };
 use std::{
     collections::{
         HashMap,
+        HashSet,
         VecDeque,
     },
     fmt::{Debug, Display},
+    path::PathBuf,
     ops::Index,
 };
 use rustdoc_types::*;
@@ -51,13 +58,23 @@ pub struct ModuleId(pub CanonId);
 /// Lazy cache for use in traversing graphs of rustdoc JSON items across multiple crates.
 pub struct GraphCache<'a> {
     pub cli_args: &'a CliArgs,
+    pub config: &'a Config,
     // maps crate name -> crate index
     crate_lookup: HashMap<String, usize>,
     // maps crate index -> data about the crate
     crates: Vec<CrateEntry>,
+    // number of non-fatal errors encountered and reported during traversals
+    analysis_errors: usize,
+    // persistent cache of crates across runs, unless disabled
+    disk_cache: Option<DiskCache>,
+    // maps crate key, as resolve_crate looks it up -> path of its rustdoc JSON, for crates built by
+    // prebuild_dependencies which haven't been loaded yet
+    prebuilt: HashMap<String, PathBuf>,
 }
 
 struct CrateEntry {
+    // name of the crate, normalized to use underscores
+    name: String,
     // crate's rustdoc JSON output
     rustdoc_json: CrateRustdocJsonCell,
     // Id within this rustdoc JSON index of the module item representing the crate root
@@ -75,6 +92,14 @@ struct CrateEntry {
     //
     // exploits rustdoc JSON Ids being distributed near zero by being a vec rather than hash map
     import_cache: Vec<Option<HashMap<String, CanonId>>>,
+    // all items which can be imported from this crate, once computed
+    public_items: Option<HashMap<CanonId, BfsNode>>,
+    // resolve_cache and import_cache entries loaded from the disk cache, which are copied into them
+    // when first looked up
+    persisted: PersistedTables,
+    // hash of the rustdoc JSON as loaded from or stored in the disk cache, if it was, which
+    // persisted ids of this crate are only valid for
+    json_hash: Option<u64>,
 }
 
 #[derive(Copy, Clone)]
@@ -116,28 +141,347 @@ impl Drop for CrateRustdocJsonCell {
     }
 }
 
+/// Where a `GraphCache::bfs` traversal starts from.
+#[derive(Copy, Clone)]
+pub enum BfsStart<'b> {
+    /// Start from the root module of the crate with the given name.
+    Crate(&'b str),
+    /// Start from all items in the output of a previous traversal.
+    Hash(&'b HashMap<CanonId, BfsNode>),
+}
+
+/// An item discovered by a `GraphCache::bfs` traversal.
+#[derive(Debug, Clone)]
+pub struct BfsNode {
+    /// Display path by which the item was discovered.
+    pub path: String,
+    /// The item it was discovered from, and why, unless it's where the traversal started.
+    pub parent: Option<(CanonId, EdgeKind)>,
+    /// Other ways the item was reached after it was discovered, if collecting them.
+    pub alternatives: Vec<BfsAlternative>,
+    /// Whether every way the item was reached is as a supertrait, including ways beyond the
+    /// alternatives collected.
+    pub only_supertrait: bool,
+}
+
+/// A way an item was reached by a `GraphCache::bfs` traversal other than the one it was discovered
+/// by.
+#[derive(Debug, Clone)]
+pub struct BfsAlternative {
+    /// Display path by which the item was reached.
+    pub path: String,
+    /// The item it was reached from, and why.
+    pub parent: CanonId,
+    pub edge: EdgeKind,
+}
+
+/// Reason a bfs linker linked one item to another.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+pub enum EdgeKind {
+    ModuleItem,
+    GlobImport,
+    Field,
+    Variant,
+    FieldType,
+    Impl,
+    TraitImpl,
+    ImplTrait,
+    SelfType,
+    ImplItem,
+    TraitItem,
+    SuperTrait,
+    GenericBound,
+    ParamType,
+    ReturnType,
+    AliasedType,
+    ItemType,
+    AssocType,
+}
+
+impl EdgeKind {
+    /// Human-readable description of the edge.
+    pub fn describe(self) -> &'static str {
+        match self {
+            EdgeKind::ModuleItem => "module item",
+            EdgeKind::GlobImport => "glob import",
+            EdgeKind::Field => "field",
+            EdgeKind::Variant => "enum variant",
+            EdgeKind::FieldType => "field type",
+            EdgeKind::Impl => "impl block",
+            EdgeKind::TraitImpl => "trait implementation",
+            EdgeKind::ImplTrait => "implemented trait",
+            EdgeKind::SelfType => "impl self type",
+            EdgeKind::ImplItem => "impl item",
+            EdgeKind::TraitItem => "trait item",
+            EdgeKind::SuperTrait => "supertrait",
+            EdgeKind::GenericBound => "generic bound",
+            EdgeKind::ParamType => "parameter type",
+            EdgeKind::ReturnType => "return type",
+            EdgeKind::AliasedType => "aliased type",
+            EdgeKind::ItemType => "type",
+            EdgeKind::AssocType => "associated type",
+        }
+    }
+}
+
+/// One hop of a witness chain, as produced by `witness_chain`.
+#[derive(Debug, Clone)]
+pub struct Hop {
+    pub id: CanonId,
+    pub path: String,
+    /// How this item was reached from the previous hop, unless it's the first hop.
+    pub edge: Option<EdgeKind>,
+}
+
+/// Witness chain for the given alternative way of reaching the given item: the witness chain of
+/// the item it was reached from, followed by the item itself.
+pub fn alternative_witness_chain(
+    nodes: &HashMap<CanonId, BfsNode>,
+    id: CanonId,
+    alternative: &BfsAlternative,
+) -> Vec<Hop> {
+    let mut chain = witness_chain(nodes, alternative.parent);
+    chain.push(Hop {
+        id,
+        path: alternative.path.clone(),
+        edge: Some(alternative.edge),
+    });
+    chain
+}
+
+/// Follow the parent pointers of the given bfs output from the given item back to where the
+/// traversal started, and return the hops in order from the start to the given item.
+pub fn witness_chain(nodes: &HashMap<CanonId, BfsNode>, mut id: CanonId) -> Vec<Hop> {
+    let mut chain = Vec::new();
+    loop {
+        let node = &nodes[&id];
+        chain.push(Hop {
+            id,
+            path: node.path.clone(),
+            edge: node.parent.map(|(_, edge)| edge),
+        });
+        match node.parent {
+            Some((parent, _)) => id = parent,
+            None => break,
+        }
+    }
+    chain.reverse();
+    chain
+}
+
 #[derive(Default)]
-pub struct BfsLinker(VecDeque<Id>);
+pub struct BfsLinker {
+    links: VecDeque<(Id, EdgeKind)>,
+    // how the item being linked from was reached, unless it's where the traversal started
+    reached_by: Option<EdgeKind>,
+}
 
 impl BfsLinker {
-    pub fn link(&mut self, id: Id) {
-        self.0.push_back(id);
+    /// How the item being linked from was reached, unless it's where the traversal started.
+    pub fn reached_by(&self) -> Option<EdgeKind> {
+        self.reached_by
+    }
+
+    pub fn link(&mut self, id: Id, edge: EdgeKind) {
+        self.links.push_back((id, edge));
     }
 
-    pub fn link_all(&mut self, ids: &[Id]) {
+    pub fn link_all(&mut self, ids: &[Id], edge: EdgeKind) {
         for &id in ids {
-            self.link(id);
+            self.link(id, edge);
         }
     }
 }
 
 impl<'a> GraphCache<'a> {
-    pub fn new(cli_args: &'a CliArgs) -> Self {
+    pub fn new(cli_args: &'a CliArgs, config: &'a Config, disk_cache: Option<DiskCache>) -> Self {
         GraphCache {
             cli_args,
+            config,
             crate_lookup: Default::default(),
             crates: Default::default(),
+            analysis_errors: 0,
+            disk_cache,
+            prebuilt: Default::default(),
+        }
+    }
+
+    /// Build the rustdoc JSON of all direct dependencies of the given root packages up front, in
+    /// parallel, rather than one by one as they're first referenced during traversals, and then
+    /// that of the dependencies they re-export, and so on. Crates which are ignored, supplied
+    /// pre-built or already in the disk cache are skipped. Failures are reported but not fatal,
+    /// since crates which weren't built are then built as usual.
+    pub fn prebuild_dependencies(&mut self, root_packages: &[String]) {
+        let mut dependencies = match self.cli_args.direct_dependencies(root_packages) {
+            Ok(dependencies) => dependencies,
+            Err(e) => {
+                eprintln!("Not building dependencies in parallel: {:?}", e);
+                return;
+            }
+        };
+        // the key resolve_crate looks each crate up by, which is that of its alias, if any
+        let config = self.config;
+        let crate_key = |dependency: &DirectDependency| config.crate_aliases
+            .get(&dependency.lib_name)
+            .unwrap_or(&dependency.lib_name)
+            .replace('-', "_");
+        loop {
+            dependencies.retain(|dependency| {
+                let crate_key = crate_key(dependency);
+                !STDLIBS.contains(&crate_key.as_str())
+                    && !self.config.ignored_crates.contains(&crate_key)
+                    && !self.crate_lookup.contains_key(&crate_key)
+                    && !self.prebuilt.contains_key(&crate_key)
+                    && self.cli_args.supplied_rustdoc_json(&crate_key).is_none()
+                    && !self.disk_cache.as_ref()
+                        .is_some_and(|disk_cache| disk_cache.has_rustdoc_json(&crate_key))
+            });
+            if dependencies.is_empty() {
+                return;
+            }
+            let packages = dependencies.iter()
+                .map(DirectDependency::spec)
+                .collect::<Vec<_>>();
+            match self.cli_args.prebuild_rustdoc_json(&packages) {
+                Ok(doc_dir) => for dependency in &dependencies {
+                    // named after the library crate, even if it's looked up by an alias
+                    let path = doc_dir.join(format!("{}.json", dependency.lib_name));
+                    self.prebuilt.insert(crate_key(dependency), path);
+                },
+                Err(e) => {
+                    eprintln!("Failed to build dependencies in parallel: {:?}", e);
+                    return;
+                }
+            }
+
+            // the dependencies which the ones just built re-export are referenced as soon as those
+            // are traversed, so load them to find out which
+            let mut reexported = HashSet::new();
+            for dependency in &dependencies {
+                match self.resolve_crate(&dependency.lib_name) {
+                    Ok(crate_id) => reexported.extend(self.reexported_crates(crate_id.0.0.crate_idx)),
+                    Err(ResolveErr::Ignore) => (),
+                    Err(ResolveErr::Fail(e)) => {
+                        eprintln!("Failed to load {}: {:?}", dependency.lib_name, e);
+                    }
+                }
+            }
+            dependencies = match self.cli_args.dependencies_of_dependencies(&dependencies) {
+                Ok(dependencies) => dependencies,
+                Err(e) => {
+                    eprintln!("Not building re-exported dependencies in parallel: {:?}", e);
+                    return;
+                }
+            };
+            dependencies.retain(|dependency| reexported.contains(&dependency.lib_name));
+        }
+    }
+
+    // names of the crates which the given crate re-exports items of
+    fn reexported_crates(&self, crate_idx: usize) -> HashSet<String> {
+        let rustdoc_json = unsafe { self.crates[crate_idx].rustdoc_json.get() };
+        rustdoc_json.index.values()
+            .filter(|item| item.crate_id == 0 && item.visibility == Visibility::Public)
+            .filter_map(|item| match &item.inner {
+                &ItemEnum::Use(Use { id: Some(ref id), .. }) => rustdoc_json.paths.get(id)
+                    .filter(|summary| summary.crate_id != 0)
+                    .and_then(|summary| rustdoc_json.external_crates.get(&summary.crate_id))
+                    .map(|external_crate| external_crate.name.clone()),
+                &ItemEnum::ExternCrate { ref name, .. } => Some(name.clone()),
+                _ => None,
+            })
+            .collect()
+    }
+
+    /// Write the resolution tables computed for cacheable crates to the disk cache, if enabled.
+    /// Failures are reported but not fatal.
+    pub fn store_disk_cache(&self) {
+        let Some(ref disk_cache) = self.disk_cache else { return };
+        for entry in &self.crates {
+            if !disk_cache.is_cacheable(&entry.name) {
+                continue;
+            }
+            // hash of the rustdoc JSON which ids of the given crate are valid for: that of the
+            // crate as loaded, or that the persisted tables were valid for if it wasn't loaded
+            let hash = |crate_key: &str| match self.crate_lookup.get(crate_key) {
+                Some(&crate_idx) => self.crates[crate_idx].json_hash,
+                None => entry.persisted.crates.get(crate_key).copied(),
+            };
+            let mut tables = entry.persisted.clone();
+            // drop persisted entries which refer to crates whose rustdoc JSON changed since
+            let is_stale = |&(ref crate_key, _): &PersistedId|
+                hash(crate_key) != tables.crates.get(crate_key).copied();
+            let stale_resolved = tables.resolved.iter()
+                .filter(|&(_, referent)| referent.as_ref().is_some_and(is_stale))
+                .map(|(&id, _)| id)
+                .collect::<Vec<_>>();
+            let stale_namespaces = tables.namespaces.iter()
+                .filter(|&(_, namespace)| namespace.values().any(is_stale))
+                .map(|(&id, _)| id)
+                .collect::<Vec<_>>();
+            let mut changed = !stale_resolved.is_empty() || !stale_namespaces.is_empty();
+            for id in stale_resolved {
+                tables.resolved.remove(&id);
+            }
+            for id in stale_namespaces {
+                tables.namespaces.remove(&id);
+            }
+            for (i, resolved) in entry.resolve_cache.iter().enumerate() {
+                let referent = match resolved {
+                    &Some(ResolveCacheEntry::Id(id)) => Some(self.persisted_id(id)),
+                    &Some(ResolveCacheEntry::Ignore) => None,
+                    &None => continue,
+                };
+                changed |= tables.resolved.insert(Id(i as u32), referent).is_none();
+            }
+            for (i, namespace) in entry.import_cache.iter().enumerate() {
+                let Some(namespace) = namespace else { continue };
+                let namespace = namespace.iter()
+                    .map(|(name, &id)| (name.clone(), self.persisted_id(id)))
+                    .collect();
+                changed |= tables.namespaces.insert(Id(i as u32), namespace).is_none();
+            }
+            if !changed {
+                continue;
+            }
+            let referent_crates = tables.resolved.values().flatten()
+                .chain(tables.namespaces.values().flat_map(HashMap::values))
+                .map(|&(ref crate_key, _)| crate_key);
+            tables.crates = [&entry.name].into_iter()
+                .chain(referent_crates)
+                .filter_map(|crate_key| Some((crate_key.clone(), hash(crate_key)?)))
+                .collect();
+            if let Err(e) = disk_cache.store_tables(&entry.name, &tables) {
+                eprintln!("Failed to cache resolution tables of {}: {:?}", entry.name, e);
+            }
+        }
+    }
+
+    // convert an id to the form it's stored in the disk cache in
+    fn persisted_id(&self, id: CanonId) -> PersistedId {
+        (self.crates[id.0.crate_idx].name.clone(), id.0.item_id)
+    }
+
+    // convert an id from the form it's stored in the disk cache in as part of the tables of the
+    // crate with the given index, loading the crate it refers to if necessary. None if the id isn't
+    // valid for that crate's rustdoc JSON as loaded.
+    fn unpersist_id(
+        &mut self,
+        tables_crate_idx: usize,
+        &(ref crate_key, item_id): &PersistedId,
+    ) -> Option<CanonId> {
+        let hash = *self.crates[tables_crate_idx].persisted.crates.get(crate_key)?;
+        let crate_idx = self.resolve_crate(crate_key).ok()?.0.0.crate_idx;
+        if self.crates[crate_idx].json_hash != Some(hash) {
+            return None;
         }
+        Some(CanonId(AbsId { crate_idx, item_id }))
+    }
+
+    /// Number of non-fatal errors encountered during traversals so far, which may have caused
+    /// results to be incomplete.
+    pub fn analysis_errors(&self) -> usize {
+        self.analysis_errors
     }
 
     #[allow(dead_code)] // TODO this is for debugging while building the project
@@ -150,45 +494,68 @@ impl<'a> GraphCache<'a> {
             })
     }
 
+    #[allow(clippy::manual_next_back)]
     pub fn bfs(
         &mut self,
         mut link: impl FnMut(&Item, &mut BfsLinker),
-        start_hash: Option<&HashMap<CanonId, String>>,
+        start: BfsStart,
         require_public: bool,
-    ) -> Result<HashMap<CanonId, String>, Error>
+    ) -> Result<HashMap<CanonId, BfsNode>, Error>
     {
         let mut queue: VecDeque<CanonId> = Default::default();
-        let mut hash: HashMap<CanonId, String> = start_hash.cloned().unwrap_or_default();
+        let mut hash: HashMap<CanonId, BfsNode> = Default::default();
 
-        if let Some(start_hash) = start_hash {
-            queue.extend(start_hash.keys().copied());
-        } else {
-            let root_crate_name = self.cli_args.root_package()?;
-            let root_id = self.resolve_crate(&root_crate_name)
-                .map_err(|e| match e {
-                    ResolveErr::Fail(e) => e,
-                    ResolveErr::Ignore => eyre!("Root crate is ignored (huh?)")
-                })?;
-            queue.push_back(root_id.0);
-            hash.insert(root_id.0, root_crate_name.replace('-', "_"));
+        match start {
+            BfsStart::Hash(start_hash) => {
+                // in a deterministic order, so that which way of reaching an item is found first
+                // doesn't change between runs
+                let mut start_ids = start_hash.keys().copied().collect::<Vec<_>>();
+                start_ids.sort_by(|id1, id2| start_hash[id1].path.cmp(&start_hash[id2].path));
+                queue.extend(start_ids);
+                hash.extend(start_hash.iter().map(|(&id, node)| (id, node.clone())));
+            }
+            BfsStart::Crate(root_crate_name) => {
+                let root_id = self.resolve_crate(root_crate_name)
+                    .map_err(|e| match e {
+                        ResolveErr::Fail(e) => e,
+                        ResolveErr::Ignore => eyre!("Root crate is ignored (huh?)")
+                    })?;
+                queue.push_back(root_id.0);
+                hash.insert(root_id.0, BfsNode {
+                    path: root_crate_name.replace('-', "_"),
+                    parent: None,
+                    alternatives: Vec::new(),
+                    only_supertrait: false,
+                });
+            }
         }
 
         let mut linker: BfsLinker = Default::default();
+        // how many ways of reaching each item to record, if more than the first
+        let max_exposures = match (require_public, self.cli_args.all_exposures) {
+            (true, _) => 1,
+            (false, true) => usize::MAX,
+            (false, false) => self.cli_args.max_exposures,
+        };
 
         while let Some(id) = queue.pop_front() {
             let rustdoc_json = unsafe { self.crates[id.0.crate_idx].rustdoc_json.get() };
             let item = rustdoc_json.index.get(&id.0.item_id).unwrap();
 
+            linker.reached_by = hash[&id].parent.map(|(_, edge)| edge);
             link(item, &mut linker);
 
-            while let Some(iid2) = linker.0.pop_front() {
+            while let Some((iid2, edge)) = linker.links.pop_front() {
                 let is_public = rustdoc_json.index.get(&iid2)
                     .is_some_and(|item|
                         item.visibility == Visibility::Public
                         || (item.visibility == Visibility::Default && match &item.inner {
                             &ItemEnum::AssocType { .. } => true, // are we being too generous here?
+                            // trait methods and the items of trait impls
+                            &ItemEnum::Function(_) => true,
+                            &ItemEnum::AssocConst { .. } => true,
                             &ItemEnum::Variant(_) => true, // are we being too generous here?
-                            &ItemEnum::Impl(_) => true, // are we being too generous here?
+                            &ItemEnum::Impl(_) => true, // further checked by impl_is_observable
                             _ => false,
                             // TODO: the doc comment for rustdoc_types::Default is wrong here
                         })
@@ -197,11 +564,39 @@ impl<'a> GraphCache<'a> {
                     // skip private item
                     continue;
                 }
+                if require_public && !self.config.hidden_importable
+                    && rustdoc_json.index.get(&iid2).is_some_and(is_hidden)
+                {
+                    // skip item which is not a part of the stable API
+                    continue;
+                }
+                if let Some(&ItemEnum::Impl(ref impl_)) = rustdoc_json.index.get(&iid2)
+                    .map(|item| &item.inner)
+                {
+                    // not while finding importable items, since that's what this relies on
+                    if !require_public && !self.impl_is_observable(id.0.same_crate(iid2), impl_) {
+                        // skip impl which is not a part of the API surface
+                        continue;
+                    }
+                }
 
                 const PATH_MODE: bool = false;
 
                 match self.resolve(id.0.same_crate(iid2), true) {
-                    Ok(id2) => if !hash.contains_key(&id2) {
+                    Ok(id2) => {
+                        if let Some(node2) = hash.get_mut(&id2) {
+                            node2.only_supertrait &= edge == EdgeKind::SuperTrait;
+                        }
+                        let is_alternative = match hash.get(&id2) {
+                            None => false,
+                            Some(node2) if node2.parent.is_some()
+                                && node2.alternatives.len() + 1 < max_exposures
+                                && node2.parent != Some((id, edge))
+                                && !node2.alternatives.iter()
+                                    .any(|alt| alt.parent == id && alt.edge == edge) => true,
+                            Some(_) => continue,
+                        };
+
                         // TODO: split out into function
                         let item2_name = rustdoc_json.index.get(&iid2)
                             .map(|item2| item2.name.clone().map(Some).map(Ok)
@@ -209,9 +604,8 @@ impl<'a> GraphCache<'a> {
                                     &ItemEnum::Impl(Impl { trait_: None, /* ref for_, TODO */ .. }) =>
                                         //Ok(Some(format!("`impl {:?}`", for_))),
                                         Ok(None),
-                                    &ItemEnum::Impl(Impl { trait_: Some(ref trait_), /* ref for_, TODO */ .. }) =>
-                                        //Ok(Some(format!("`impl {:?} for {:?}`", trait_, for_))),
-                                        Ok(Some(format!("`<_ as {}<{:?}>>`", trait_.name, trait_.args))),
+                                    &ItemEnum::Impl(Impl { trait_: Some(_), .. }) =>
+                                        Ok(None), // replaced with a qualified path below
                                     &ItemEnum::ExternCrate { ref name, rename: None } =>
                                         Ok(Some(match PATH_MODE {
                                             false => name.clone(),
@@ -248,19 +642,47 @@ impl<'a> GraphCache<'a> {
                                         Some(format!("`{}`", DisplayPath(&item_summary.path)))
                                     ),
                             })?;
-                        let item2_path = item2_name
-                            .map(|item2_name| format!("{}::{}", hash[&id], item2_name))
-                            .unwrap_or_else(|| hash[&id].clone());
-                        hash.insert(id2, item2_path);
+                        let parent_path = &hash[&id].path;
+                        let item2_path = match rustdoc_json.index.get(&iid2).map(|item2| &item2.inner) {
+                            Some(&ItemEnum::Impl(Impl { trait_: Some(ref trait_), ref for_, .. })) =>
+                                if edge == EdgeKind::TraitImpl {
+                                    // reached from the trait, so the parent path names the trait
+                                    let args = trait_.args.as_ref()
+                                        .map(|args| DisplayGenericArgs(args).to_string())
+                                        .unwrap_or_default();
+                                    format!("<{} as {}{}>", DisplayType(for_), parent_path, args)
+                                } else {
+                                    // reached from the self type, so the parent path names it
+                                    format!("<{} as {}>", parent_path, DisplayTypePath(trait_))
+                                },
+                            _ => item2_name
+                                .map(|item2_name| format!("{}::{}", parent_path, item2_name))
+                                .unwrap_or_else(|| parent_path.clone()),
+                        };
+                        if is_alternative {
+                            hash.get_mut(&id2).unwrap().alternatives.push(BfsAlternative {
+                                path: item2_path,
+                                parent: id,
+                                edge,
+                            });
+                            continue;
+                        }
+                        hash.insert(id2, BfsNode {
+                            path: item2_path,
+                            parent: Some((id, edge)),
+                            alternatives: Vec::new(),
+                            only_supertrait: edge == EdgeKind::SuperTrait,
+                        });
                         if is_public {
                             // TODO: split the set here rather than requiring 2 weird phases
                             queue.push_back(id2);
                         }
                     },
                     Err(ResolveErr::Fail(e)) => {
-                        let e = e.wrap_err(eyre!("Resolving child of {}", hash[&id]));
+                        let e = e.wrap_err(eyre!("Resolving child of {}", hash[&id].path));
                         eprintln!("{:?}", e);
-                        //return Err(e.wrap_err(eyre!("Resolving child of {}", hash[&id])))}, // TODO: better error reporting here
+                        self.analysis_errors += 1;
+                        //return Err(e.wrap_err(eyre!("Resolving child of {}", hash[&id].path)))}, // TODO: better error reporting here
                     }
                     Err(ResolveErr::Ignore) => (),
                 }
@@ -301,16 +723,23 @@ impl<'a> GraphCache<'a> {
     }
 
     // resolve the canonical id of the root of the crate with the given name
-    pub fn resolve_crate(&mut self, mut crate_name: &str) -> Result<ModuleId, ResolveErr> {
-        if crate_name == "webpki" {
-            crate_name = "rustls_webpki"; // TODO
-        }
+    pub fn resolve_crate(&mut self, crate_name: &str) -> Result<ModuleId, ResolveErr> {
+        let config = self.config;
+        let crate_name = config.crate_aliases.get(crate_name)
+            .map(String::as_str)
+            .unwrap_or(crate_name);
 
-        if STDLIBS.contains(&crate_name) {
+        if STDLIBS.contains(&crate_name)
+            || config.ignored_crates.iter().any(|ignored| ignored == crate_name)
+        {
             return Err(ResolveErr::Ignore);
         }
 
-        if let Some(&crate_idx) = self.crate_lookup.get(crate_name) {
+        // normalize so that workspace members named with hyphens share cache entries with
+        // references to them from other crates
+        let crate_key = crate_name.replace('-', "_");
+
+        if let Some(&crate_idx) = self.crate_lookup.get(&crate_key) {
             // cached
             return Ok(self.module_id(AbsId {
                 crate_idx,
@@ -319,23 +748,55 @@ impl<'a> GraphCache<'a> {
         }
 
         let crate_idx = self.crates.len();
-        let rustdoc_json = self.cli_args.build_rustdoc_json(crate_name)?;
+        let disk_cache = self.disk_cache.as_ref()
+            .filter(|disk_cache| disk_cache.is_cacheable(&crate_key))
+            .filter(|_| self.cli_args.supplied_rustdoc_json(crate_name).is_none());
+        let (rustdoc_json, persisted, json_hash) = match disk_cache
+            .and_then(|disk_cache| disk_cache.load_rustdoc_json(&crate_key))
+        {
+            Some((rustdoc_json, hash)) => (
+                rustdoc_json,
+                disk_cache.and_then(|disk_cache| disk_cache.load_tables(&crate_key))
+                    .filter(|tables| tables.crates.get(&crate_key) == Some(&hash))
+                    .unwrap_or_default(),
+                Some(hash),
+            ),
+            None => {
+                let prebuilt = self.prebuilt.remove(&crate_key).filter(|path| path.is_file());
+                let rustdoc_json = match prebuilt {
+                    Some(path) => load_rustdoc_json(&path)?,
+                    None => self.cli_args.rustdoc_json(crate_name)?,
+                };
+                let json_hash = disk_cache.and_then(|disk_cache| disk_cache
+                    .store_rustdoc_json(&crate_key, &rustdoc_json)
+                    .unwrap_or_else(|e| {
+                        eprintln!("Failed to cache rustdoc JSON of {}: {:?}", crate_key, e);
+                        None
+                    }));
+                (rustdoc_json, PersistedTables::default(), json_hash)
+            }
+        };
         let root_module = rustdoc_json.index.values()
             .find(|&item|
                 matches!(&item.inner, &ItemEnum::Module(Module { is_crate: true, .. })))
             .ok_or_else(|| eyre!("No root module in rustdoc JSON of {:?} crate", crate_name))?
             .id;
         self.crates.push(CrateEntry {
+            name: crate_key.clone(),
             rustdoc_json: rustdoc_json.into(),
             root_module,
             resolve_cache: Default::default(),
             import_cache: Default::default(),
+            public_items: None,
+            persisted,
+            json_hash,
         });
-        self.crate_lookup.insert(crate_name.to_owned(), crate_idx);
+        self.crate_lookup.insert(crate_key, crate_idx);
         Ok(self.module_id(AbsId { crate_idx, item_id: root_module }))
     }
 
     // resolve the canonical referent of the given id (with caching)
+    #[allow(clippy::needless_return)]
     pub fn resolve(&mut self, id: AbsId, filter_public: bool) -> Result<CanonId, ResolveErr> {
         if let Some(&entry) = self.crates[id.crate_idx].resolve_cache
             .get(id.item_id.0 as usize)
@@ -348,7 +809,10 @@ impl<'a> GraphCache<'a> {
             };
         } else {
             // must cache
-            let result = self.resolve_inner(id, filter_public);
+            let result = match self.resolve_persisted(id) {
+                Some(result) => result,
+                None => self.resolve_inner(id, filter_public),
+            };
             let cache = &mut self.crates[id.crate_idx].resolve_cache;
             while cache.len() <= id.item_id.0 as usize {
                 cache.push(None);
@@ -368,6 +832,15 @@ impl<'a> GraphCache<'a> {
         }
     }
 
+    // look up the canonical referent of the given id in the tables loaded from the disk cache
+    fn resolve_persisted(&mut self, id: AbsId) -> Option<Result<CanonId, ResolveErr>> {
+        let referent = self.crates[id.crate_idx].persisted.resolved.get(&id.item_id)?.clone();
+        Some(match referent {
+            Some(referent) => Ok(self.unpersist_id(id.crate_idx, &referent)?),
+            None => Err(ResolveErr::Ignore),
+        })
+    }
+
     // resolve the canonical referent of the given id (no caching)
     fn resolve_inner(&mut self, id: AbsId, filter_public: bool) -> Result<CanonId, ResolveErr> {
         if let Some(&entry) = self.crates[id.crate_idx].resolve_cache
@@ -430,8 +903,8 @@ impl<'a> GraphCache<'a> {
     fn resolve_path(&mut self, id: ModuleId, path: &[String]) -> Result<CanonId, ResolveErr> {
         let mut id = id.0;
         for path_part in path {
-            if path_part == "__private" {
-                return Err(ResolveErr::Ignore); // TODO handle this better
+            if self.config.opaque_modules.contains(path_part) {
+                return Err(ResolveErr::Ignore);
             }
 
             // build namespace
@@ -455,7 +928,10 @@ impl<'a> GraphCache<'a> {
             .get(id.0.item_id.0 as usize)
             .and_then(|opt| opt.as_ref());
         if cached.is_none() {
-            let namespace = self.module_namespace_inner(id)?;
+            let namespace = match self.module_namespace_persisted(id) {
+                Some(namespace) => namespace,
+                None => self.module_namespace_inner(id)?,
+            };
             let cache = &mut self.crates[id.0.crate_idx].import_cache;
             while cache.len() <= id.0.item_id.0 as usize {
                 cache.push(None);
@@ -468,6 +944,17 @@ impl<'a> GraphCache<'a> {
             .unwrap())
     }
 
+    // look up the namespace of the given module in the tables loaded from the disk cache
+    fn module_namespace_persisted(&mut self, id: CanonId) -> Option<HashMap<String, CanonId>> {
+        let namespace = self.crates[id.0.crate_idx].persisted.namespaces.get(&id.0.item_id)?.clone();
+        namespace.iter()
+            .map(|(name, referent)| Some((
+                name.clone(),
+                self.unpersist_id(id.0.crate_idx, referent)?,
+            )))
+            .collect()
+    }
+
     // like module_namespace but without no caching
     fn module_namespace_inner(&mut self, id: CanonId) -> Result<HashMap<String, CanonId>, Error> {
         // ensure the module_id refers to a module item
@@ -517,6 +1004,166 @@ impl<'a> GraphCache<'a> {
         }
         Ok(namespace)
     }
+
+    /// All items which can be imported from the crate the given item is defined in (cached).
+    pub fn crate_public_items(&mut self, id: CanonId) -> Result<&HashMap<CanonId, BfsNode>, Error> {
+        let crate_idx = id.0.crate_idx;
+        if self.crates[crate_idx].public_items.is_none() {
+            let crate_name = self.crates[crate_idx].name.clone();
+            let public_items = self.bfs(link_importable, BfsStart::Crate(&crate_name), true)?;
+            self.crates[crate_idx].public_items = Some(public_items);
+        }
+        Ok(self.crates[crate_idx].public_items.as_ref().unwrap())
+    }
+
+    // whether the referent of the given id can be named from outside of its defining crate.
+    // items in ignored crates are considered nameable, as are items which fail to resolve, so as
+    // to err on the side of treating things as part of the API surface. failures are reported and
+    // counted as analysis errors.
+    fn is_nameable(&mut self, id: AbsId) -> bool {
+        let result = match self.resolve(id, false) {
+            Ok(id) => self.crate_public_items(id)
+                .map(|public_items| public_items.contains_key(&id)),
+            Err(ResolveErr::Ignore) => return true,
+            Err(ResolveErr::Fail(e)) => Err(e),
+        };
+        result.unwrap_or_else(|e| {
+            eprintln!("{:?}", e.wrap_err("Determining whether an item is nameable"));
+            self.analysis_errors += 1;
+            true
+        })
+    }
+
+    // whether all types named by the given type can be named from outside of their defining
+    // crates. `crate_idx` is the crate the type appears in.
+    fn type_is_nameable(&mut self, crate_idx: usize, type_: &Type) -> bool {
+        match type_ {
+            &Type::ResolvedPath(ref path) => self.path_is_nameable(crate_idx, path),
+            &Type::DynTrait(ref dyn_trait) => dyn_trait.traits.iter()
+                .all(|trait_| self.path_is_nameable(crate_idx, &trait_.trait_)),
+            &Type::FunctionPointer(ref function_pointer) => function_pointer.sig.inputs.iter()
+                .map(|&(_, ref type_)| type_)
+                .chain(&function_pointer.sig.output)
+                .all(|type_| self.type_is_nameable(crate_idx, type_)),
+            &Type::Tuple(ref types) =>
+                types.iter().all(|type_| self.type_is_nameable(crate_idx, type_)),
+            &Type::Slice(ref type_)
+            | &Type::Array { ref type_, .. }
+            | &Type::Pat { ref type_, .. }
+            | &Type::RawPointer { ref type_, .. }
+            | &Type::BorrowedRef { ref type_, .. } => self.type_is_nameable(crate_idx, type_),
+            &Type::QualifiedPath { ref args, ref self_type, ref trait_, .. } =>
+                self.generic_args_are_nameable(crate_idx, args)
+                && self.type_is_nameable(crate_idx, self_type)
+                && trait_.as_ref().is_none_or(|trait_| self.path_is_nameable(crate_idx, trait_)),
+            &Type::Generic(_)
+            | &Type::Primitive(_)
+            | &Type::ImplTrait(_)
+            | &Type::Infer => true,
+        }
+    }
+
+    // whether the item a path refers to and all types in its generic arguments can be named from
+    // outside of their defining crates
+    fn path_is_nameable(&mut self, crate_idx: usize, path: &Path) -> bool {
+        self.is_nameable(AbsId { crate_idx, item_id: path.id })
+            && path.args.as_ref().is_none_or(|args| self.generic_args_are_nameable(crate_idx, args))
+    }
+
+    fn generic_args_are_nameable(&mut self, crate_idx: usize, args: &GenericArgs) -> bool {
+        match args {
+            &GenericArgs::AngleBracketed { ref args, ref constraints } =>
+                args.iter().all(|arg| match arg {
+                    &GenericArg::Type(ref type_) => self.type_is_nameable(crate_idx, type_),
+                    &GenericArg::Lifetime(_) | &GenericArg::Const(_) | &GenericArg::Infer => true,
+                })
+                && constraints.iter().all(|constraint| match &constraint.binding {
+                    &AssocItemConstraintKind::Equality(Term::Type(ref type_)) =>
+                        self.type_is_nameable(crate_idx, type_),
+                    _ => true,
+                }),
+            &GenericArgs::Parenthesized { ref inputs, ref output } => inputs.iter()
+                .chain(output)
+                .all(|type_| self.type_is_nameable(crate_idx, type_)),
+        }
+    }
+
+    // whether an impl block is a part of the API surface, which is the case for inherent impls
+    // and for trait impls for which both the trait and the self type can be named from outside
+    fn impl_is_observable(&mut self, id: AbsId, impl_: &Impl) -> bool {
+        let Some(ref trait_) = impl_.trait_ else { return true };
+        self.is_nameable(id.same_crate(trait_.id)) && self.type_is_nameable(id.crate_idx, &impl_.for_)
+    }
+
+    /// Whether the given item is a trait which only its own crate can implement, because it is not
+    /// importable from outside it and has no blanket impls which downstream types could fall under.
+    ///
+    /// Only meaningful for traits already known to not be importable.
+    pub fn is_sealed_trait(&self, id: CanonId) -> bool {
+        let &ItemEnum::Trait(ref trait_) = &self[id].inner else { return false };
+        let rustdoc_json = unsafe { self.crates[id.0.crate_idx].rustdoc_json.get() };
+        trait_.implementations.iter().all(|impl_id| {
+            let Some(&ItemEnum::Impl(ref impl_)) = rustdoc_json.index.get(impl_id)
+                .map(|item| &item.inner)
+                else { return true };
+            impl_.blanket_impl.is_none() && !matches!(&impl_.for_, &Type::Generic(_))
+        })
+    }
+
+    /// The module the given item is declared in, if any.
+    pub fn parent_module(&self, id: CanonId) -> Option<CanonId> {
+        let rustdoc_json = unsafe { self.crates[id.0.crate_idx].rustdoc_json.get() };
+        rustdoc_json.index.values()
+            .find(|item| matches!(
+                &item.inner,
+                &ItemEnum::Module(ref module) if module.items.contains(&id.0.item_id)
+            ))
+            .map(|item| CanonId(id.0.same_crate(item.id)))
+    }
+
+    /// Name of the crate the given item is defined in.
+    pub fn crate_name(&self, id: CanonId) -> &str {
+        &self.crates[id.0.crate_idx].name
+    }
+
+    /// Summary of the given item from its own crate's rustdoc JSON, including its canonical path
+    /// within its defining crate, if rustdoc knows one.
+    pub fn item_summary(&self, id: CanonId) -> Option<&ItemSummary> {
+        let rustdoc_json = unsafe { self.crates[id.0.crate_idx].rustdoc_json.get() };
+        rustdoc_json.paths.get(&id.0.item_id)
+    }
+}
+
+// whether an item is `#[doc(hidden)]`, or is a module which rustdoc stripped from documentation
+fn is_hidden(item: &Item) -> bool {
+    matches!(&item.inner, &ItemEnum::Module(Module { is_stripped: true, .. }))
+    || item.attrs.iter().any(|attr| is_doc_hidden_attr(attr))
+}
+
+// whether an attribute is a `#[doc(..)]` attribute with `hidden` as one of its arguments
+fn is_doc_hidden_attr(attr: &str) -> bool {
+    let Some(args) = attr.trim()
+        .strip_prefix("#[doc(")
+        .and_then(|attr| attr.strip_suffix(")]"))
+        else { return false };
+    // split on commas which aren't within string literals, like `alias = "a, b"`
+    let mut in_string = false;
+    let mut escaped = false;
+    let mut start = 0;
+    let mut hidden = false;
+    for (i, c) in args.char_indices().chain([(args.len(), ',')]) {
+        match c {
+            _ if escaped => escaped = false,
+            '\\' if in_string => escaped = true,
+            '"' => in_string = !in_string,
+            ',' if !in_string => {
+                hidden |= args[start..i].trim() == "hidden";
+                start = i + 1;
+            }
+            _ => (),
+        }
+    }
+    hidden
 }
 
 impl<'a> Index<CanonId> for GraphCache<'a> {
@@ -570,3 +1217,20 @@ impl<T> WrapErr2<T> for Result<T, ResolveErr> {
         self.map_err(|e| e.wrap_err(f()))
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::is_doc_hidden_attr;
+
+    #[test]
+    fn doc_hidden_attr() {
+        assert!(is_doc_hidden_attr("#[doc(hidden)]"));
+        assert!(is_doc_hidden_attr("#[doc(inline, hidden)]"));
+        assert!(is_doc_hidden_attr("#[doc(hidden, alias = \"x\")]"));
+        assert!(!is_doc_hidden_attr("#[doc(alias = \"hidden\")]"));
+        assert!(!is_doc_hidden_attr("#[doc(alias = \"a, hidden\")]"));
+        assert!(!is_doc_hidden_attr("#[doc(alias = \"\\\", hidden\")]"));
+        assert!(!is_doc_hidden_attr("#[doc(inline)]"));
+        assert!(!is_doc_hidden_attr("#[cfg(hidden)]"));
+    }
+}
@@ -4,9 +4,26 @@ use crate::{
     item_graph::{
         GraphCache,
         BfsLinker,
+        BfsStart,
+        BfsNode,
+        CanonId,
+        EdgeKind,
+        witness_chain,
+        alternative_witness_chain,
     },
+    pretty_print::DisplayPath,
+    report::{Finding, CanonItem, Exposure, Location, Report, Format},
+    baseline::Baseline,
+    config::Config,
+    fixes::FixSuggester,
+    apply_fixes::apply_fixes,
+    build_rustdoc_json::init_rustdoc_flags,
     error::*,
 };
+use std::{
+    collections::HashMap,
+    process::ExitCode,
+};
 use clap::Parser;
 use rustdoc_types::*;
 
@@ -15,16 +32,25 @@ pub mod error {
 }
 
 mod cli_args;
+mod apply_fixes;
+mod baseline;
 mod build_rustdoc_json;
 mod cargo_metadata;
+mod config;
+mod disk_cache;
+mod fixes;
+mod format_version;
 mod item_graph;
 mod pretty_print;
+mod report;
+mod sarif;
 
 // bfs linker that finds all items which can be imported from the root crate
 fn link_importable(item: &Item, bfs: &mut BfsLinker) {
     match &item.inner {
-        &ItemEnum::Module(ref module) => bfs.link_all(&module.items),
-        &ItemEnum::Use(Use { is_glob: true, id: Some(id), .. }) => bfs.link(id),
+        &ItemEnum::Module(ref module) => bfs.link_all(&module.items, EdgeKind::ModuleItem),
+        &ItemEnum::Use(Use { is_glob: true, id: Some(id), .. }) =>
+            bfs.link(id, EdgeKind::GlobImport),
         _ => (),
     }
 }
@@ -38,8 +64,8 @@ fn link_visible(item: &Item, bfs: &mut BfsLinker) {
         &ItemEnum::Use(Use { is_glob: false, .. }) => unreachable!("not canonical"),
         &ItemEnum::Union(ref inner) => {
             link_visible_generics(&inner.generics, bfs);
-            bfs.link_all(&inner.fields);
-            bfs.link_all(&inner.impls);
+            bfs.link_all(&inner.fields, EdgeKind::Field);
+            bfs.link_all(&inner.impls, EdgeKind::Impl);
         }
         &ItemEnum::Struct(ref inner) => {
             match &inner.kind {
@@ -47,19 +73,19 @@ fn link_visible(item: &Item, bfs: &mut BfsLinker) {
                 &StructKind::Tuple(ref fields) =>
                     for field in fields {
                         if let &Some(field) = field {
-                            bfs.link(field);
+                            bfs.link(field, EdgeKind::Field);
                         }
                     },
-                &StructKind::Plain { ref fields, .. } => bfs.link_all(fields),
+                &StructKind::Plain { ref fields, .. } => bfs.link_all(fields, EdgeKind::Field),
             }
             link_visible_generics(&inner.generics, bfs);
-            bfs.link_all(&inner.impls);
+            bfs.link_all(&inner.impls, EdgeKind::Impl);
         }
-        &ItemEnum::StructField(ref type_) => link_visible_type(type_, bfs),
+        &ItemEnum::StructField(ref type_) => link_visible_type(type_, EdgeKind::FieldType, bfs),
         &ItemEnum::Enum(ref inner) => {
             link_visible_generics(&inner.generics, bfs);
-            bfs.link_all(&inner.variants);
-            bfs.link_all(&inner.impls);
+            bfs.link_all(&inner.variants, EdgeKind::Variant);
+            bfs.link_all(&inner.impls, EdgeKind::Impl);
         }
         &ItemEnum::Variant(ref inner) => {
             match &inner.kind {
@@ -67,10 +93,10 @@ fn link_visible(item: &Item, bfs: &mut BfsLinker) {
                 &VariantKind::Tuple(ref fields) =>
                     for field in fields {
                         if let &Some(field) = field {
-                            bfs.link(field);
+                            bfs.link(field, EdgeKind::Field);
                         }
                     },
-                &VariantKind::Struct { ref fields, .. } => bfs.link_all(fields),
+                &VariantKind::Struct { ref fields, .. } => bfs.link_all(fields, EdgeKind::Field),
             }
         }
         &ItemEnum::Function(ref inner) => {
@@ -78,145 +104,169 @@ fn link_visible(item: &Item, bfs: &mut BfsLinker) {
             link_visible_generics(&inner.generics, bfs);
         }
         &ItemEnum::Trait(ref inner) => {
-            bfs.link_all(&inner.items);
+            bfs.link_all(&inner.items, EdgeKind::TraitItem);
             link_visible_generics(&inner.generics, bfs);
             for bound in &inner.bounds {
-                link_visible_generic_bound(bound, bfs);
+                link_visible_generic_bound(bound, EdgeKind::SuperTrait, bfs);
+            }
+            // only the ones which are observable get traversed
+            bfs.link_all(&inner.implementations, EdgeKind::TraitImpl);
+        }
+        &ItemEnum::TraitAlias(ref inner) => {
+            link_visible_generics(&inner.generics, bfs);
+            for bound in &inner.params {
+                link_visible_generic_bound(bound, EdgeKind::SuperTrait, bfs);
             }
-            // TODO: inner.implementations exists, but we need to have a way of knowing whether a
-            //       trait impl is effectively public
         }
-        &ItemEnum::TraitAlias(_) => unimplemented!(),
         &ItemEnum::Impl(ref inner) => {
-            // TODO: we need to have a way of knowing whether a trait impl is effectively public
+            // only reached if the impl is observable, see GraphCache::impl_is_observable
             link_visible_generics(&inner.generics, bfs);
-            // TODO: impl.trait_ exists
-            // TODO: impl.for_ exists
-            bfs.link_all(&inner.items);
-            // TODO: blanket_impl exists, and is lacking documentation
+            if let &Some(ref trait_) = &inner.trait_ {
+                link_visible_path(trait_, EdgeKind::ImplTrait, bfs);
+            }
+            // the trait alone gives no way of obtaining values of the types implementing it, so
+            // they are only visible if something else exposes them
+            if bfs.reached_by() != Some(EdgeKind::TraitImpl) {
+                link_visible_type(&inner.for_, EdgeKind::SelfType, bfs);
+                if let &Some(ref blanket_impl) = &inner.blanket_impl {
+                    link_visible_type(blanket_impl, EdgeKind::SelfType, bfs);
+                }
+            }
+            bfs.link_all(&inner.items, EdgeKind::ImplItem);
         }
         &ItemEnum::TypeAlias(ref inner) => {
-            link_visible_type(&inner.type_, bfs);
+            link_visible_type(&inner.type_, EdgeKind::AliasedType, bfs);
             link_visible_generics(&inner.generics, bfs);
         }
-        &ItemEnum::Constant { ref type_, .. } => link_visible_type(type_, bfs),
-        &ItemEnum::Static(ref inner) => link_visible_type(&inner.type_, bfs),
-        &ItemEnum::ExternType => unimplemented!(),
+        &ItemEnum::Constant { ref type_, .. } => link_visible_type(type_, EdgeKind::ItemType, bfs),
+        &ItemEnum::Static(ref inner) => link_visible_type(&inner.type_, EdgeKind::ItemType, bfs),
+        &ItemEnum::ExternType => (), // opaque, so nothing further is exposed
         &ItemEnum::Macro(_) => (),
         &ItemEnum::ProcMacro(_) => (),
         &ItemEnum::Primitive(_) => (),
-        &ItemEnum::AssocConst { ref type_, .. } => link_visible_type(type_, bfs),
+        &ItemEnum::AssocConst { ref type_, .. } => link_visible_type(type_, EdgeKind::ItemType, bfs),
         &ItemEnum::AssocType { ref generics, ref bounds, ref type_ } => {
             link_visible_generics(generics, bfs);
             for bound in bounds {
-                link_visible_generic_bound(bound, bfs);
+                link_visible_generic_bound(bound, EdgeKind::AssocType, bfs);
             }
             if let Some(type_) = type_ {
-                link_visible_type(type_, bfs);
+                link_visible_type(type_, EdgeKind::AssocType, bfs);
             }
         }
     }
 }
 
+// the edge kind given to the helpers below is used for all items they link, including ones
+// nested within generic arguments
+
 fn link_visible_generics(generics: &Generics, bfs: &mut BfsLinker) {
+    let edge = EdgeKind::GenericBound;
     for param in &generics.params {
-        link_visible_generic_param(param, bfs);
+        link_visible_generic_param(param, edge, bfs);
     }
     for where_predicate in &generics.where_predicates {
         match where_predicate {
             &WherePredicate::BoundPredicate { ref type_, ref bounds, ref generic_params } => {
-                link_visible_type(type_, bfs);
+                link_visible_type(type_, edge, bfs);
                 for bound in bounds {
-                    link_visible_generic_bound(bound, bfs);
+                    link_visible_generic_bound(bound, edge, bfs);
                 }
                 for param in generic_params {
-                    link_visible_generic_param(param, bfs);
+                    link_visible_generic_param(param, edge, bfs);
                 }
             }
             &WherePredicate::LifetimePredicate { .. } => (),
             &WherePredicate::EqPredicate { ref lhs, ref rhs } => {
-                link_visible_type(lhs, bfs);
-                link_visible_term(rhs, bfs);
+                link_visible_type(lhs, edge, bfs);
+                link_visible_term(rhs, edge, bfs);
             }
         }
     }
 }
 
-fn link_visible_term(term: &Term, bfs: &mut BfsLinker) {
+fn link_visible_term(term: &Term, edge: EdgeKind, bfs: &mut BfsLinker) {
     if let &Term::Type(ref type_) = term {
-        link_visible_type(type_, bfs);
+        link_visible_type(type_, edge, bfs);
     }
 }
 
-fn link_visible_generic_param(param: &GenericParamDef, bfs: &mut BfsLinker) {
+fn link_visible_generic_param(param: &GenericParamDef, edge: EdgeKind, bfs: &mut BfsLinker) {
     if let &GenericParamDefKind::Type { ref bounds, ref default, .. } = &param.kind {
         for bound in bounds {
-            link_visible_generic_bound(bound, bfs);
+            link_visible_generic_bound(bound, edge, bfs);
         }
         if let &Some(ref default) = default {
-            link_visible_type(default, bfs);
+            link_visible_type(default, edge, bfs);
         }
     }
 }
 
-fn link_visible_generic_bound(bound: &GenericBound, bfs: &mut BfsLinker) {
-    if let &GenericBound::TraitBound { ref trait_, ref generic_params, .. } = bound {
-        link_visible_path(&trait_, bfs);
-        for param2 in generic_params {
-            link_visible_generic_param(param2, bfs);
+#[allow(clippy::needless_borrow)]
+fn link_visible_generic_bound(bound: &GenericBound, edge: EdgeKind, bfs: &mut BfsLinker) {
+    match bound {
+        &GenericBound::TraitBound { ref trait_, ref generic_params, .. } => {
+            link_visible_path(&trait_, edge, bfs);
+            for param2 in generic_params {
+                link_visible_generic_param(param2, edge, bfs);
+            }
         }
+        &GenericBound::Outlives(_) => (),
+        // precise capturing `use<..>` can only name lifetimes and generic params of the enclosing
+        // item, the bounds of which are linked along with that item's generics
+        &GenericBound::Use(_) => (),
     }
-    // TODO GenericBound::Use, once stable
 }
 
-fn link_visible_path(path: &Path, bfs: &mut BfsLinker) {
-    bfs.link(path.id);
+fn link_visible_path(path: &Path, edge: EdgeKind, bfs: &mut BfsLinker) {
+    bfs.link(path.id, edge);
     if let &Some(ref args) = &path.args {
-        link_visible_generic_args(&**args, bfs);
+        link_visible_generic_args(&**args, edge, bfs);
     }
 }
 
-fn link_visible_generic_args(args: &GenericArgs, bfs: &mut BfsLinker) {
+fn link_visible_generic_args(args: &GenericArgs, edge: EdgeKind, bfs: &mut BfsLinker) {
     match args {
         &GenericArgs::AngleBracketed { ref args, ref constraints } => {
             for arg in args {
                 match arg {
                     &GenericArg::Lifetime(_) => (),
-                    &GenericArg::Type(ref type_) => link_visible_type(type_, bfs),
+                    &GenericArg::Type(ref type_) => link_visible_type(type_, edge, bfs),
                     &GenericArg::Const(_) => (),
                     &GenericArg::Infer => (),
                 }
             }
             for constraint in constraints {
-                link_visible_generic_args(&constraint.args, bfs);
+                link_visible_generic_args(&constraint.args, edge, bfs);
                 match &constraint.binding {
-                    &AssocItemConstraintKind::Equality(ref term) => link_visible_term(term, bfs),
+                    &AssocItemConstraintKind::Equality(ref term) =>
+                        link_visible_term(term, edge, bfs),
                     &AssocItemConstraintKind::Constraint(ref bounds) =>
                         for bound in bounds {
-                            link_visible_generic_bound(bound, bfs);
+                            link_visible_generic_bound(bound, edge, bfs);
                         },
                 }
             }
         }
         &GenericArgs::Parenthesized { ref inputs, ref output } => {
             for input in inputs {
-                link_visible_type(input, bfs);
+                link_visible_type(input, edge, bfs);
             }
             if let Some(output) = output {
-                link_visible_type(output, bfs);
+                link_visible_type(output, edge, bfs);
             }
         }
     }
 }
 
-fn link_visible_type(type_: &Type, bfs: &mut BfsLinker) {
+fn link_visible_type(type_: &Type, edge: EdgeKind, bfs: &mut BfsLinker) {
     match type_ {
-        &Type::ResolvedPath(ref path) => link_visible_path(path, bfs),
+        &Type::ResolvedPath(ref path) => link_visible_path(path, edge, bfs),
         &Type::DynTrait(ref dyn_trait) =>
             for trait_ in &dyn_trait.traits {
-                link_visible_path(&trait_.trait_, bfs);
+                link_visible_path(&trait_.trait_, edge, bfs);
                 for param in &trait_.generic_params {
-                    link_visible_generic_param(param, bfs);
+                    link_visible_generic_param(param, edge, bfs);
                 }
             },
         &Type::Generic(_) => (),
@@ -224,29 +274,29 @@ fn link_visible_type(type_: &Type, bfs: &mut BfsLinker) {
         &Type::FunctionPointer(ref function_pointer) => {
             link_visible_function_signature(&function_pointer.sig, bfs);
             for param in &function_pointer.generic_params {
-                link_visible_generic_param(param, bfs);
+                link_visible_generic_param(param, edge, bfs);
             }
         }
         &Type::Tuple(ref types) => {
             for type_ in types {
-                link_visible_type(type_, bfs);
+                link_visible_type(type_, edge, bfs);
             }
         }
-        &Type::Slice(ref type_) => link_visible_type(&**type_, bfs),
-        &Type::Array { ref type_, .. } => link_visible_type(&**type_, bfs),
-        &Type::Pat { .. } => unimplemented!(),
+        &Type::Slice(ref type_) => link_visible_type(&**type_, edge, bfs),
+        &Type::Array { ref type_, .. } => link_visible_type(&**type_, edge, bfs),
+        &Type::Pat { ref type_, .. } => link_visible_type(&**type_, edge, bfs),
         &Type::ImplTrait(ref bounds) =>
             for bound in bounds {
-                link_visible_generic_bound(bound, bfs)
+                link_visible_generic_bound(bound, edge, bfs)
             },
         &Type::Infer => (),
-        &Type::RawPointer { ref type_, .. } => link_visible_type(&**type_, bfs),
-        &Type::BorrowedRef { ref type_, .. } => link_visible_type(&**type_, bfs),
+        &Type::RawPointer { ref type_, .. } => link_visible_type(&**type_, edge, bfs),
+        &Type::BorrowedRef { ref type_, .. } => link_visible_type(&**type_, edge, bfs),
         &Type::QualifiedPath { ref args, ref self_type, ref trait_, .. } => {
-            link_visible_generic_args(&**args, bfs);
-            link_visible_type(&**self_type, bfs);
+            link_visible_generic_args(&**args, edge, bfs);
+            link_visible_type(&**self_type, edge, bfs);
             if let &Some(ref trait_) = trait_ {
-                link_visible_path(trait_, bfs);
+                link_visible_path(trait_, edge, bfs);
             }
         }
     }
@@ -254,44 +304,257 @@ fn link_visible_type(type_: &Type, bfs: &mut BfsLinker) {
 
 fn link_visible_function_signature(sig: &FunctionSignature, bfs: &mut BfsLinker) {
     for &(_, ref type_) in &sig.inputs {
-        link_visible_type(type_, bfs);
+        link_visible_type(type_, EdgeKind::ParamType, bfs);
     }
     if let &Some(ref type_) = &sig.output {
-        link_visible_type(type_, bfs);
+        link_visible_type(type_, EdgeKind::ReturnType, bfs);
     }
 }
 
-fn main() -> Result<()> {
-    let args = CliArgs::parse();
-    color_eyre::install()?;
-    let mut graph = GraphCache::new(&args);
-    //dbg!(graph.resolve2("quinn", &["StreamId"])?);
-    let importable = graph.bfs(link_importable, None, true)?;
+// result of traversing a single root crate
+struct Analysis {
+    root_package: String,
+    // all items which can be imported from the root crate
+    importable: HashMap<CanonId, BfsNode>,
+    // all items which are a part of the root crate's API surface, including importable ones
+    visible: HashMap<CanonId, BfsNode>,
+}
+
+fn analyze(graph: &mut GraphCache, root_package: &str) -> Result<Analysis> {
+    let importable = graph.bfs(link_importable, BfsStart::Crate(root_package), true)?;
     /*println!("importable:");
-    let mut paths = importable.values().cloned().collect::<Vec<_>>();
+    let mut paths = importable.values().map(|node| node.path.clone()).collect::<Vec<_>>();
     paths.sort();
     for path in &paths {
         println!("- {}", path);
     }*/
-    let visible = graph.bfs(link_visible, Some(&importable), false)?;
-    println!("visible but not importable:");
-    let mut paths = visible.iter()
-        .filter(|&(&id, _)| !importable.contains_key(&id))
-        .filter(|&(&id, _)| match &graph[id].inner {
-            &ItemEnum::Union(_) => true,
-            &ItemEnum::Struct(_) => true,
-            &ItemEnum::Enum(_) => true,
-            &ItemEnum::Trait(_) => true,
-            &ItemEnum::TraitAlias(_) => unimplemented!(),
-            &ItemEnum::TypeAlias(_) => true,
-            &ItemEnum::ExternType => unimplemented!(),
-            _ => false,
-        })
-        .map(|(_, path)| path.clone())
+    let visible = graph.bfs(link_visible, BfsStart::Hash(&importable), false)?;
+    Ok(Analysis {
+        root_package: root_package.to_owned(),
+        importable,
+        visible,
+    })
+}
+
+// find all items which are a part of the given root crate's API surface but cannot be imported
+// from it, sorted by the path through which they are visible
+fn find_leaks(
+    graph: &mut GraphCache,
+    fixes: &mut FixSuggester,
+    config: &Config,
+    analysis: &Analysis,
+) -> Result<Vec<Finding>> {
+    let root_crate = analysis.root_package.replace('-', "_");
+    let mut findings = Vec::new();
+    for (&id, node) in &analysis.visible {
+        if analysis.importable.contains_key(&id) {
+            continue;
+        }
+        let is_leak = matches!(
+            &graph[id].inner,
+            &ItemEnum::Union(_)
+            | &ItemEnum::Struct(_)
+            | &ItemEnum::Enum(_)
+            | &ItemEnum::Trait(_)
+            | &ItemEnum::TraitAlias(_)
+            | &ItemEnum::TypeAlias(_)
+            | &ItemEnum::ExternType
+        );
+        if !is_leak {
+            continue;
+        }
+        let item = CanonItem::new(graph, id);
+        let canonical_path = item.path.as_ref().map(|path| path.join("::"));
+        let paths = canonical_path.iter().map(String::as_str).chain([node.path.as_str()]);
+        if config.is_ignored_item(paths) {
+            continue;
+        }
+        // a private supertrait keeps downstream crates from implementing the subtrait, which is
+        // the intent rather than an oversight, unless it's also visible some other way
+        let sealed = node.only_supertrait && graph.is_sealed_trait(id);
+        let exposures = [(node.path.clone(), witness_chain(&analysis.visible, id))].into_iter()
+            .chain(node.alternatives.iter().map(|alternative| (
+                alternative.path.clone(),
+                alternative_witness_chain(&analysis.visible, id, alternative),
+            )))
+            .map(|(visible_path, witness)| Exposure {
+                visible_path,
+                location: witness.iter()
+                    .rev()
+                    .skip(1)
+                    .find_map(|hop| graph[hop.id].span.as_ref())
+                    .map(Location::from),
+                witness,
+            })
+            .collect::<Vec<_>>();
+        findings.push(Finding {
+            root_crate: root_crate.clone(),
+            item,
+            definition: graph[id].span.as_ref().map(Location::from),
+            sealed,
+            fixes: if sealed {
+                Vec::new()
+            } else {
+                fixes.suggest(graph, &analysis.root_package, &analysis.importable, &exposures[0].witness)?
+            },
+            exposures,
+        });
+    }
+    findings.sort_by_cached_key(|finding| (finding.item.crate_name.clone(), finding.item_path()));
+    Ok(findings)
+}
+
+// print the witness chain for every visible item with the given display path or canonical path,
+// returning whether any were found
+fn explain(graph: &GraphCache, analysis: &Analysis, path: &str) -> bool {
+    let mut ids = analysis.visible.iter()
+        .filter(|&(&id, node)|
+            node.path == path
+            || graph.item_summary(id)
+                .is_some_and(|summary| DisplayPath(&summary.path).to_string() == path))
+        .map(|(&id, node)| (node.path.as_str(), id))
         .collect::<Vec<_>>();
-    paths.sort();
-    for path in &paths {
-        println!("- {}", path);
+    ids.sort_by_key(|&(path, _)| path);
+    for &(_, id) in &ids {
+        println!("witness chain for {}:", path);
+        for (i, hop) in witness_chain(&analysis.visible, id).iter().enumerate() {
+            let arrow = if i == 0 { "" } else { "-> " };
+            let edge = hop.edge.map(EdgeKind::describe).unwrap_or("crate root");
+            println!("  {}{} ({})", arrow, hop.path, edge);
+        }
+        if analysis.importable.contains_key(&id) {
+            println!("which is importable");
+        } else {
+            println!("which is not importable");
+        }
+    }
+    !ids.is_empty()
+}
+
+/// Exit code when `--deny` is given and there are findings.
+const EXIT_FINDINGS: u8 = 1;
+/// Exit code when analysis fails or, if `--deny` is given, is incomplete due to errors.
+const EXIT_ERROR: u8 = 2;
+
+fn main() -> ExitCode {
+    run().unwrap_or_else(|e| {
+        eprintln!("Error: {:?}", e);
+        ExitCode::from(EXIT_ERROR)
+    })
+}
+
+fn run() -> Result<ExitCode> {
+    let args = CliArgs::parse();
+    color_eyre::install()?;
+    init_rustdoc_flags();
+    let root_packages = args.root_packages()?;
+    let configs = args.configs()?;
+    let format = args.format
+        .or(configs.for_packages(&root_packages).format)
+        .unwrap_or(Format::Human);
+    // check the toolchain up front rather than after building dependencies if any root package
+    // will be built. otherwise it's only checked before building a dependency, since with rustdoc
+    // JSON supplied for every crate it may not even be installed.
+    if root_packages.iter().any(|root_package| args.supplied_rustdoc_json(root_package).is_none()) {
+        args.check_toolchain()?;
+    }
+    // each root package is traversed with its own configuration, since that affects how paths
+    // resolve. root packages with the same configuration share a graph, so that dependencies are
+    // only loaded once.
+    let mut groups: Vec<(Config, Vec<String>)> = Vec::new();
+    for root_package in &root_packages {
+        let mut config = configs.for_packages([root_package]);
+        config.hidden_importable |= args.hidden_importable;
+        match groups.iter_mut().find(|&&mut (ref group_config, _)| *group_config == config) {
+            Some(&mut (_, ref mut group)) => group.push(root_package.clone()),
+            None => groups.push((config, vec![root_package.clone()])),
+        }
+    }
+    let mut graphs = Vec::new();
+    // pairs of graph index and analysis
+    let mut analyses = Vec::new();
+    for &(ref config, ref group) in &groups {
+        let disk_cache = args.disk_cache(config).unwrap_or_else(|e| {
+            eprintln!("Not using cache: {:?}", e);
+            None
+        });
+        let mut graph = GraphCache::new(&args, config, disk_cache);
+        if !args.no_prebuild {
+            graph.prebuild_dependencies(group);
+        }
+        //dbg!(graph.resolve2("quinn", &["StreamId"])?);
+        for root_package in group {
+            analyses.push((graphs.len(), analyze(&mut graph, root_package)?));
+        }
+        graph.store_disk_cache();
+        graphs.push(graph);
+    }
+    analyses.sort_by_key(|&(_, ref analysis)| root_packages.iter()
+        .position(|root_package| *root_package == analysis.root_package));
+    if let Some(ref path) = args.explain {
+        let mut found = false;
+        for &(graph_idx, ref analysis) in &analyses {
+            found |= explain(&graphs[graph_idx], analysis, path);
+        }
+        ensure!(found, "No item visible through the API with path {}", path);
+        return Ok(ExitCode::SUCCESS);
+    }
+    // one per graph, since they cache items of the graph
+    let mut fixes = graphs.iter().map(|_| FixSuggester::default()).collect::<Vec<_>>();
+    let mut roots = analyses.iter()
+        .map(|&(graph_idx, ref analysis)| Ok((
+            analysis.root_package.clone(),
+            find_leaks(
+                &mut graphs[graph_idx],
+                &mut fixes[graph_idx],
+                &groups[graph_idx].0,
+                analysis,
+            )?,
+        )))
+        .collect::<Result<Vec<_>>>()?;
+    if !args.show_sealed {
+        for &mut (_, ref mut findings) in &mut roots {
+            findings.retain(|finding| !finding.sealed);
+        }
+    }
+    if let Some(ref path) = args.write_baseline {
+        let mut baseline = Baseline::from_findings(&roots);
+        if path.exists() {
+            baseline.keep_unanalyzed(Baseline::load(path)?, &roots);
+        }
+        baseline.write(path)?;
+    }
+    let mut fixed = Vec::new();
+    if let Some(ref path) = args.baseline {
+        fixed = Baseline::load(path)?.apply(&mut roots);
+    }
+    if args.fix {
+        let fix_roots = analyses.iter()
+            .zip(&roots)
+            .map(|(&(graph_idx, _), &(_, ref findings))| (&graphs[graph_idx], findings.as_slice()))
+            .collect::<Vec<_>>();
+        apply_fixes(&args, &fix_roots)?;
+        if args.dry_run {
+            return Ok(ExitCode::SUCCESS);
+        }
+    }
+    let report = Report {
+        roots,
+        grouped: args.workspace,
+        fixed,
+        workspace_root: args.workspace_root()?,
+        repository_root: args.repository_root()?,
+    };
+    report::output(format, &report)?;
+    if args.deny {
+        let analysis_errors = graphs.iter().map(GraphCache::analysis_errors).sum::<usize>();
+        if analysis_errors > 0 {
+            eprintln!("analysis incomplete due to {} error(s)", analysis_errors);
+            return Ok(ExitCode::from(EXIT_ERROR));
+        }
+        if report.roots.iter().flat_map(|&(_, ref findings)| findings).any(|finding| !finding.sealed) {
+            return Ok(ExitCode::from(EXIT_FINDINGS));
+        }
     }
     /*/
     let stream_id_id = graph.resolve2("quinn", &["StreamId"])?;
@@ -313,5 +576,5 @@ fn main() -> Result<()> {
         }
     }
     */
-    Ok(())
+    Ok(ExitCode::SUCCESS)
 }
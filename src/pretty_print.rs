@@ -1,6 +1,19 @@
 //! Pretty printing utilities.
 
 use std::fmt::{self, Formatter, Display};
+use rustdoc_types::{
+    Type,
+    GenericArgs,
+    GenericArg,
+    GenericBound,
+    GenericParamDef,
+    TraitBoundModifier,
+    AssocItemConstraint,
+    AssocItemConstraintKind,
+    Term,
+    Abi,
+    FunctionSignature,
+};
 
 pub struct DisplayPath<'a>(pub &'a [String]);
 
@@ -16,3 +29,360 @@ impl<'a> Display for DisplayPath<'a> {
     }
 }
 
+
+/// Displays a rustdoc type in Rust syntax.
+pub struct DisplayType<'a>(pub &'a Type);
+
+/// Displays a rustdoc path, including generic arguments, in Rust syntax.
+pub struct DisplayTypePath<'a>(pub &'a rustdoc_types::Path);
+
+/// Displays rustdoc generic arguments in Rust syntax, including the angle brackets or parentheses.
+pub struct DisplayGenericArgs<'a>(pub &'a GenericArgs);
+
+/// Displays a rustdoc generic bound in Rust syntax.
+pub struct DisplayGenericBound<'a>(pub &'a GenericBound);
+
+impl<'a> Display for DisplayType<'a> {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        match self.0 {
+            &Type::ResolvedPath(ref path) => write!(f, "{}", DisplayTypePath(path)),
+            &Type::DynTrait(ref dyn_trait) => {
+                f.write_str("dyn ")?;
+                for (i, poly_trait) in dyn_trait.traits.iter().enumerate() {
+                    if i > 0 {
+                        f.write_str(" + ")?;
+                    }
+                    write_for_params(f, &poly_trait.generic_params)?;
+                    write!(f, "{}", DisplayTypePath(&poly_trait.trait_))?;
+                }
+                if let &Some(ref lifetime) = &dyn_trait.lifetime {
+                    write!(f, " + {}", lifetime)?;
+                }
+                Ok(())
+            }
+            &Type::Generic(ref name) => f.write_str(name),
+            &Type::Primitive(ref name) => f.write_str(name),
+            &Type::FunctionPointer(ref function_pointer) => {
+                write_for_params(f, &function_pointer.generic_params)?;
+                let header = &function_pointer.header;
+                if header.is_const {
+                    f.write_str("const ")?;
+                }
+                if header.is_async {
+                    f.write_str("async ")?;
+                }
+                if header.is_unsafe {
+                    f.write_str("unsafe ")?;
+                }
+                write_abi(f, &header.abi)?;
+                f.write_str("fn")?;
+                write_signature(f, &function_pointer.sig)
+            }
+            &Type::Tuple(ref types) => {
+                f.write_str("(")?;
+                write_separated(f, types.iter().map(DisplayType), ", ")?;
+                if types.len() == 1 {
+                    f.write_str(",")?;
+                }
+                f.write_str(")")
+            }
+            &Type::Slice(ref type_) => write!(f, "[{}]", DisplayType(type_)),
+            &Type::Array { ref type_, ref len } => write!(f, "[{}; {}]", DisplayType(type_), len),
+            &Type::Pat { ref type_, ref __pat_unstable_do_not_use } =>
+                write!(f, "{} is {}", DisplayType(type_), __pat_unstable_do_not_use),
+            &Type::ImplTrait(ref bounds) => {
+                f.write_str("impl ")?;
+                write_separated(f, bounds.iter().map(DisplayGenericBound), " + ")
+            }
+            &Type::Infer => f.write_str("_"),
+            &Type::RawPointer { is_mutable, ref type_ } => {
+                write!(f, "*{} ", if is_mutable { "mut" } else { "const" })?;
+                write_pointee(f, type_)
+            }
+            &Type::BorrowedRef { ref lifetime, is_mutable, ref type_ } => {
+                f.write_str("&")?;
+                if let &Some(ref lifetime) = lifetime {
+                    write!(f, "{} ", lifetime)?;
+                }
+                if is_mutable {
+                    f.write_str("mut ")?;
+                }
+                write_pointee(f, type_)
+            }
+            &Type::QualifiedPath { ref name, ref args, ref self_type, ref trait_ } => {
+                match trait_ {
+                    &Some(ref trait_) => write!(
+                        f, "<{} as {}>", DisplayType(self_type), DisplayTypePath(trait_),
+                    )?,
+                    &None => write!(f, "{}", DisplayType(self_type))?,
+                }
+                write!(f, "::{}{}", name, DisplayGenericArgs(args))
+            }
+        }
+    }
+}
+
+impl<'a> Display for DisplayTypePath<'a> {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        f.write_str(&self.0.name)?;
+        if let &Some(ref args) = &self.0.args {
+            write!(f, "{}", DisplayGenericArgs(args))?;
+        }
+        Ok(())
+    }
+}
+
+impl<'a> Display for DisplayGenericArgs<'a> {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        match self.0 {
+            &GenericArgs::AngleBracketed { ref args, ref constraints } => {
+                if args.is_empty() && constraints.is_empty() {
+                    return Ok(());
+                }
+                f.write_str("<")?;
+                write_separated(f, args.iter().map(DisplayGenericArg), ", ")?;
+                if !args.is_empty() && !constraints.is_empty() {
+                    f.write_str(", ")?;
+                }
+                write_separated(f, constraints.iter().map(DisplayConstraint), ", ")?;
+                f.write_str(">")
+            }
+            &GenericArgs::Parenthesized { ref inputs, ref output } => {
+                f.write_str("(")?;
+                write_separated(f, inputs.iter().map(DisplayType), ", ")?;
+                f.write_str(")")?;
+                if let &Some(ref output) = output {
+                    write!(f, " -> {}", DisplayType(output))?;
+                }
+                Ok(())
+            }
+        }
+    }
+}
+
+impl<'a> Display for DisplayGenericBound<'a> {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        match self.0 {
+            &GenericBound::TraitBound { ref trait_, ref generic_params, ref modifier } => {
+                write_for_params(f, generic_params)?;
+                match modifier {
+                    &TraitBoundModifier::None => (),
+                    &TraitBoundModifier::Maybe => f.write_str("?")?,
+                    &TraitBoundModifier::MaybeConst => f.write_str("~const ")?,
+                }
+                write!(f, "{}", DisplayTypePath(trait_))
+            }
+            &GenericBound::Outlives(ref lifetime) => f.write_str(lifetime),
+            &GenericBound::Use(ref params) => {
+                f.write_str("use<")?;
+                write_separated(f, params.iter(), ", ")?;
+                f.write_str(">")
+            }
+        }
+    }
+}
+
+struct DisplayGenericArg<'a>(&'a GenericArg);
+
+impl<'a> Display for DisplayGenericArg<'a> {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        match self.0 {
+            &GenericArg::Lifetime(ref lifetime) => f.write_str(lifetime),
+            &GenericArg::Type(ref type_) => write!(f, "{}", DisplayType(type_)),
+            &GenericArg::Const(ref constant) => f.write_str(&constant.expr),
+            &GenericArg::Infer => f.write_str("_"),
+        }
+    }
+}
+
+struct DisplayConstraint<'a>(&'a AssocItemConstraint);
+
+impl<'a> Display for DisplayConstraint<'a> {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        write!(f, "{}{}", self.0.name, DisplayGenericArgs(&self.0.args))?;
+        match &self.0.binding {
+            &AssocItemConstraintKind::Equality(Term::Type(ref type_)) =>
+                write!(f, " = {}", DisplayType(type_)),
+            &AssocItemConstraintKind::Equality(Term::Constant(ref constant)) =>
+                write!(f, " = {}", constant.expr),
+            &AssocItemConstraintKind::Constraint(ref bounds) => {
+                f.write_str(": ")?;
+                write_separated(f, bounds.iter().map(DisplayGenericBound), " + ")
+            }
+        }
+    }
+}
+
+fn write_separated<T: Display>(
+    f: &mut Formatter,
+    items: impl Iterator<Item=T>,
+    separator: &str,
+) -> fmt::Result {
+    for (i, item) in items.enumerate() {
+        if i > 0 {
+            f.write_str(separator)?;
+        }
+        write!(f, "{}", item)?;
+    }
+    Ok(())
+}
+
+// write a `for<'a, ..> ` higher-ranked binder, if there are any params
+fn write_for_params(f: &mut Formatter, params: &[GenericParamDef]) -> fmt::Result {
+    if params.is_empty() {
+        return Ok(());
+    }
+    f.write_str("for<")?;
+    write_separated(f, params.iter().map(|param| &param.name), ", ")?;
+    f.write_str("> ")
+}
+
+fn write_abi(f: &mut Formatter, abi: &Abi) -> fmt::Result {
+    let (name, unwind) = match abi {
+        &Abi::Rust => return Ok(()),
+        &Abi::C { unwind } => ("C", unwind),
+        &Abi::Cdecl { unwind } => ("cdecl", unwind),
+        &Abi::Stdcall { unwind } => ("stdcall", unwind),
+        &Abi::Fastcall { unwind } => ("fastcall", unwind),
+        &Abi::Aapcs { unwind } => ("aapcs", unwind),
+        &Abi::Win64 { unwind } => ("win64", unwind),
+        &Abi::SysV64 { unwind } => ("sysv64", unwind),
+        &Abi::System { unwind } => ("system", unwind),
+        &Abi::Other(ref name) => return write!(f, "extern {:?} ", name),
+    };
+    write!(f, "extern \"{}{}\" ", name, if unwind { "-unwind" } else { "" })
+}
+
+// write the type behind a reference or pointer, which needs parentheses if it has several bounds,
+// since `&dyn A + 'a` would parse as `(&dyn A) + 'a`
+fn write_pointee(f: &mut Formatter, type_: &Type) -> fmt::Result {
+    let has_several_bounds = match type_ {
+        &Type::DynTrait(ref dyn_trait) =>
+            dyn_trait.traits.len() + dyn_trait.lifetime.iter().count() > 1,
+        &Type::ImplTrait(ref bounds) => bounds.len() > 1,
+        _ => false,
+    };
+    if has_several_bounds {
+        write!(f, "({})", DisplayType(type_))
+    } else {
+        write!(f, "{}", DisplayType(type_))
+    }
+}
+
+// write the parenthesized inputs and output of a function signature
+fn write_signature(f: &mut Formatter, sig: &FunctionSignature) -> fmt::Result {
+    f.write_str("(")?;
+    write_separated(f, sig.inputs.iter().map(|&(_, ref type_)| DisplayType(type_)), ", ")?;
+    if sig.is_c_variadic {
+        f.write_str(if sig.inputs.is_empty() { "..." } else { ", ..." })?;
+    }
+    f.write_str(")")?;
+    if let &Some(ref output) = &sig.output {
+        write!(f, " -> {}", DisplayType(output))?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rustdoc_types::{Id, DynTrait, PolyTrait, Path};
+
+    fn path(name: &str, args: Vec<Type>) -> Path {
+        Path {
+            name: name.to_owned(),
+            id: Id(0),
+            args: Some(Box::new(GenericArgs::AngleBracketed {
+                args: args.into_iter().map(GenericArg::Type).collect(),
+                constraints: Vec::new(),
+            })),
+        }
+    }
+
+    fn resolved(name: &str) -> Type {
+        Type::ResolvedPath(path(name, Vec::new()))
+    }
+
+    fn dyn_trait(traits: &[&str], lifetime: Option<&str>) -> Type {
+        Type::DynTrait(DynTrait {
+            traits: traits.iter()
+                .map(|&name| PolyTrait {
+                    trait_: path(name, Vec::new()),
+                    generic_params: Vec::new(),
+                })
+                .collect(),
+            lifetime: lifetime.map(str::to_owned),
+        })
+    }
+
+    fn borrowed(type_: Type) -> Type {
+        Type::BorrowedRef { lifetime: None, is_mutable: false, type_: Box::new(type_) }
+    }
+
+    fn trait_bound(name: &str) -> GenericBound {
+        GenericBound::TraitBound {
+            trait_: path(name, Vec::new()),
+            generic_params: Vec::new(),
+            modifier: TraitBoundModifier::None,
+        }
+    }
+
+    fn display(type_: &Type) -> String {
+        DisplayType(type_).to_string()
+    }
+
+    #[test]
+    fn qualified_path() {
+        let type_ = Type::QualifiedPath {
+            name: "MultiLane".to_owned(),
+            args: Box::new(GenericArgs::AngleBracketed {
+                args: Vec::new(),
+                constraints: Vec::new(),
+            }),
+            self_type: Box::new(resolved("Connecting")),
+            trait_: Some(path("VZip", vec![Type::Generic("V".to_owned())])),
+        };
+        assert_eq!(display(&type_), "<Connecting as VZip<V>>::MultiLane");
+    }
+
+    #[test]
+    fn generic_args() {
+        let type_ = Type::ResolvedPath(path("HashMap", vec![
+            Type::Primitive("u32".to_owned()),
+            Type::Tuple(vec![Type::Primitive("u8".to_owned())]),
+        ]));
+        assert_eq!(display(&type_), "HashMap<u32, (u8,)>");
+        let type_ = Type::ResolvedPath(Path {
+            name: "Fn".to_owned(),
+            id: Id(0),
+            args: Some(Box::new(GenericArgs::Parenthesized {
+                inputs: vec![Type::Slice(Box::new(Type::Primitive("u8".to_owned())))],
+                output: Some(Type::Infer),
+            })),
+        });
+        assert_eq!(display(&type_), "Fn([u8]) -> _");
+    }
+
+    #[test]
+    fn pointee_bounds() {
+        assert_eq!(display(&borrowed(dyn_trait(&["Error"], None))), "&dyn Error");
+        assert_eq!(
+            display(&borrowed(dyn_trait(&["Error"], Some("'a")))),
+            "&(dyn Error + 'a)",
+        );
+        assert_eq!(
+            display(&borrowed(dyn_trait(&["Error", "Send"], None))),
+            "&(dyn Error + Send)",
+        );
+        let pointer = Type::RawPointer {
+            is_mutable: true,
+            type_: Box::new(dyn_trait(&["Any"], Some("'static"))),
+        };
+        assert_eq!(display(&pointer), "*mut (dyn Any + 'static)");
+        let impl_trait = Type::ImplTrait(vec![trait_bound("Read"), trait_bound("Write")]);
+        assert_eq!(display(&impl_trait), "impl Read + Write");
+        assert_eq!(display(&borrowed(impl_trait)), "&(impl Read + Write)");
+        let impl_trait = Type::ImplTrait(vec![trait_bound("Read")]);
+        assert_eq!(display(&borrowed(impl_trait)), "&impl Read");
+    }
+}
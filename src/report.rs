@@ -0,0 +1,691 @@
+//! Findings and the various formats for outputting them.
+
+use crate::{
+    error::*,
+    item_graph::{GraphCache, CanonId, Hop, EdgeKind},
+    fixes::{Fix, FixKind},
+    sarif::output_sarif,
+};
+use std::{
+    collections::HashMap,
+    fmt::{self, Display, Formatter},
+    fs,
+    path::{Path, PathBuf},
+};
+use rustdoc_types::{ItemKind, Span};
+use serde_json::{Value, json};
+use clap::ValueEnum;
+
+
+/// Version of the `--format json` output schema. Bumped whenever a change is made to it which
+/// could break consumers.
+pub const JSON_SCHEMA_VERSION: u32 = 1;
+
+#[derive(ValueEnum, Debug, Copy, Clone, PartialEq, Eq)]
+pub enum Format {
+    /// Human-readable list of paths
+    Human,
+    /// Versioned machine-readable JSON document
+    Json,
+    /// rustc-style warnings with source snippets
+    Diagnostics,
+    /// SARIF 2.1.0 log, for code scanning tools
+    Sarif,
+    /// GitHub Actions workflow commands, which annotate the source
+    Github,
+    /// Markdown tables, for pasting into pull requests
+    Markdown,
+}
+
+/// An item which is visible through a root crate's API but not importable from it.
+#[derive(Debug, Clone)]
+pub struct Finding {
+    /// Name of the root crate being analyzed, normalized to use underscores.
+    pub root_crate: String,
+    /// The leaked item.
+    pub item: CanonItem,
+    /// Where the leaked item is defined.
+    pub definition: Option<Location>,
+    /// Ways in which the item is visible through the root crate's API, up to `--max-exposures`.
+    /// Never empty, and the first one is the one found first, and thus has the shortest witness
+    /// chain.
+    pub exposures: Vec<Exposure>,
+    /// Whether the item is a sealed trait, which is not importable intentionally.
+    pub sealed: bool,
+    /// Suggested fixes, in order of preference.
+    pub fixes: Vec<Fix>,
+}
+
+impl Finding {
+    /// The exposure found first.
+    pub fn primary(&self) -> &Exposure {
+        &self.exposures[0]
+    }
+
+    /// Canonical path of the leaked item, or the path of its definition if rustdoc doesn't know
+    /// one.
+    pub fn item_path(&self) -> String {
+        self.item.definition_path.join("::")
+    }
+
+    pub fn category(&self) -> Category {
+        if self.sealed {
+            Category::SealedTrait
+        } else if self.fixes.iter().any(|fix| fix.kind == FixKind::ReexportCrate) {
+            Category::UnnameableCrate
+        } else if matches!(self.item.kind, ItemKind::Trait | ItemKind::TraitAlias) {
+            Category::UnnameableTrait
+        } else {
+            Category::UnnameableType
+        }
+    }
+}
+
+/// One way in which a leaked item is visible through a root crate's API.
+#[derive(Debug, Clone)]
+pub struct Exposure {
+    /// Path through the root crate's API by which the item is visible.
+    pub visible_path: String,
+    /// Chain of items from the root crate's root module to the leaked item.
+    pub witness: Vec<Hop>,
+    /// Where the signature which leaks the item, the nearest item before it on the witness chain
+    /// which has a source location, is defined.
+    pub location: Option<Location>,
+}
+
+impl Exposure {
+    /// The item before the leaked item on the witness chain, whose signature leaks it.
+    pub fn exposed_by(&self) -> Option<&Hop> {
+        self.witness.iter().rev().nth(1)
+    }
+}
+
+/// Kind of finding, for formats which distinguish between rules.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum Category {
+    /// A type which is not importable.
+    UnnameableType,
+    /// A trait which is not importable.
+    UnnameableTrait,
+    /// An item of a dependency which is not importable because the dependency itself is not.
+    UnnameableCrate,
+    /// A sealed trait, which is not importable intentionally.
+    SealedTrait,
+}
+
+impl Category {
+    pub const ALL: [Category; 4] = [
+        Category::UnnameableType,
+        Category::UnnameableTrait,
+        Category::UnnameableCrate,
+        Category::SealedTrait,
+    ];
+
+    /// Stable identifier.
+    pub fn id(self) -> &'static str {
+        match self {
+            Category::UnnameableType => "unnameable-type",
+            Category::UnnameableTrait => "unnameable-trait",
+            Category::UnnameableCrate => "unnameable-external-crate",
+            Category::SealedTrait => "sealed-trait",
+        }
+    }
+
+    /// One sentence description.
+    pub fn description(self) -> &'static str {
+        match self {
+            Category::UnnameableType =>
+                "A type is visible through the public API but cannot be imported from it.",
+            Category::UnnameableTrait =>
+                "A trait is visible through the public API but cannot be imported from it.",
+            Category::UnnameableCrate =>
+                "An item of a dependency is visible through the public API, but neither it nor \
+                the dependency can be imported from it.",
+            Category::SealedTrait =>
+                "A trait is only visible as a supertrait keeping downstream crates from \
+                implementing a public trait.",
+        }
+    }
+}
+
+/// Identification of an item in its defining crate.
+#[derive(Debug, Clone)]
+pub struct CanonItem {
+    /// Name of the defining crate, normalized to use underscores.
+    pub crate_name: String,
+    /// Kind of item.
+    pub kind: ItemKind,
+    /// Canonical path of the item in its defining crate, starting with the crate name, if rustdoc
+    /// knows one.
+    pub path: Option<Vec<String>>,
+    /// `path` if rustdoc knows it, or otherwise the path of the item's definition: its crate, the
+    /// modules it's declared in and its name.
+    pub definition_path: Vec<String>,
+}
+
+impl CanonItem {
+    pub fn new(graph: &GraphCache, id: CanonId) -> Self {
+        let summary = graph.item_summary(id);
+        let path = summary.map(|summary| summary.path.clone());
+        CanonItem {
+            crate_name: graph.crate_name(id).to_owned(),
+            kind: summary.map(|summary| summary.kind)
+                .unwrap_or_else(|| item_kind(&graph[id].inner)),
+            definition_path: path.clone().unwrap_or_else(|| definition_path(graph, id)),
+            path,
+        }
+    }
+}
+
+// determine the path of an item's definition from the modules it's declared in
+fn definition_path(graph: &GraphCache, id: CanonId) -> Vec<String> {
+    let name = |id: CanonId| graph[id].name.clone().unwrap_or_else(|| "_".to_owned());
+    let mut path = vec![name(id)];
+    let mut module_id = id;
+    while let Some(parent_id) = graph.parent_module(module_id) {
+        module_id = parent_id;
+        path.push(name(module_id));
+    }
+    // the crate root module is named after the crate, but not normalized
+    if module_id != id {
+        path.pop();
+    }
+    path.push(graph.crate_name(id).to_owned());
+    path.reverse();
+    path
+}
+
+/// A source code range, as given by rustdoc.
+#[derive(Debug, Clone)]
+pub struct Location {
+    /// Path of the file, relative to the workspace root for workspace members.
+    pub file: PathBuf,
+    /// 1-indexed line of the start.
+    pub line: usize,
+    /// 1-indexed column of the start.
+    pub column: usize,
+    /// 1-indexed line of the end.
+    pub end_line: usize,
+    /// 1-indexed column of the end, exclusive.
+    pub end_column: usize,
+}
+
+impl From<&Span> for Location {
+    fn from(span: &Span) -> Self {
+        // despite what rustdoc_types documents, span lines are 1-indexed in practice, whereas
+        // columns are 0-indexed
+        Location {
+            file: span.filename.clone(),
+            line: span.begin.0,
+            column: span.begin.1 + 1,
+            end_line: span.end.0,
+            end_column: span.end.1 + 1,
+        }
+    }
+}
+
+impl Display for Location {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        write!(f, "{}:{}:{}", self.file.display(), self.line, self.column)
+    }
+}
+
+// determine the kind of an item, for items which rustdoc doesn't give a summary for
+fn item_kind(inner: &rustdoc_types::ItemEnum) -> ItemKind {
+    use rustdoc_types::ItemEnum;
+    match inner {
+        &ItemEnum::Module(_) => ItemKind::Module,
+        &ItemEnum::ExternCrate { .. } => ItemKind::ExternCrate,
+        &ItemEnum::Use(_) => ItemKind::Use,
+        &ItemEnum::Union(_) => ItemKind::Union,
+        &ItemEnum::Struct(_) => ItemKind::Struct,
+        &ItemEnum::StructField(_) => ItemKind::StructField,
+        &ItemEnum::Enum(_) => ItemKind::Enum,
+        &ItemEnum::Variant(_) => ItemKind::Variant,
+        &ItemEnum::Function(_) => ItemKind::Function,
+        &ItemEnum::Trait(_) => ItemKind::Trait,
+        &ItemEnum::TraitAlias(_) => ItemKind::TraitAlias,
+        &ItemEnum::Impl(_) => ItemKind::Impl,
+        &ItemEnum::TypeAlias(_) => ItemKind::TypeAlias,
+        &ItemEnum::Constant { .. } => ItemKind::Constant,
+        &ItemEnum::Static(_) => ItemKind::Static,
+        &ItemEnum::ExternType => ItemKind::ExternType,
+        &ItemEnum::Macro(_) => ItemKind::Macro,
+        &ItemEnum::ProcMacro(ref inner) => match inner.kind {
+            rustdoc_types::MacroKind::Bang => ItemKind::Macro,
+            rustdoc_types::MacroKind::Attr => ItemKind::ProcAttribute,
+            rustdoc_types::MacroKind::Derive => ItemKind::ProcDerive,
+        },
+        &ItemEnum::Primitive(_) => ItemKind::Primitive,
+        &ItemEnum::AssocConst { .. } => ItemKind::AssocConst,
+        &ItemEnum::AssocType { .. } => ItemKind::AssocType,
+    }
+}
+
+/// Everything to be output about a run.
+pub struct Report {
+    /// Each analyzed root crate alongside its findings.
+    pub roots: Vec<(String, Vec<Finding>)>,
+    /// Whether to show which root crate human-readable findings belong to.
+    pub grouped: bool,
+    /// Baseline entries which no longer correspond to findings, as `(root crate, key)` pairs.
+    pub fixed: Vec<(String, String)>,
+    /// Root of the analyzed workspace, which relative source locations are relative to.
+    pub workspace_root: PathBuf,
+    /// Root of the repository containing the workspace, which annotations for code hosts are
+    /// relative to.
+    pub repository_root: PathBuf,
+}
+
+impl Report {
+    /// Path of a location's file relative to the repository root, or absolute if it's outside of
+    /// the repository.
+    pub fn repository_path(&self, location: &Location) -> PathBuf {
+        let path = self.workspace_root.join(&location.file);
+        path.strip_prefix(&self.repository_root)
+            .map(Path::to_owned)
+            .unwrap_or_else(|_| path.clone())
+    }
+}
+
+/// Output the report in the given format.
+pub fn output(format: Format, report: &Report) -> Result<()> {
+    match format {
+        Format::Human => output_human(report),
+        Format::Json => output_json(report)?,
+        Format::Diagnostics => output_diagnostics(report),
+        Format::Sarif => output_sarif(report)?,
+        Format::Github => output_github(report),
+        Format::Markdown => output_markdown(report)?,
+    }
+    Ok(())
+}
+
+fn output_human(report: &Report) {
+    for (i, &(ref root_package, ref findings)) in report.roots.iter().enumerate() {
+        if report.grouped {
+            if i > 0 {
+                println!();
+            }
+            println!("{}:", root_package);
+        }
+        println!("visible but not importable:");
+        let leaks = findings.iter().filter(|finding| !finding.sealed).collect::<Vec<_>>();
+        for (crate_name, leaks) in by_crate(&leaks) {
+            println!();
+            println!("defined in {}:", crate_name);
+            for finding in leaks {
+                if finding.exposures.len() == 1 {
+                    println!("- {}", finding.primary().visible_path);
+                } else {
+                    println!(
+                        "- {} (exposed through {} signatures)",
+                        finding.item_path(), finding.exposures.len(),
+                    );
+                    for exposure in &finding.exposures {
+                        println!("  - {}", exposure.visible_path);
+                    }
+                }
+            }
+        }
+        output_human_fixes(findings);
+        if findings.iter().any(|finding| finding.sealed) {
+            println!();
+            println!("sealed traits (intentionally not importable):");
+            for finding in findings.iter().filter(|finding| finding.sealed) {
+                println!("- {}", finding.primary().visible_path);
+            }
+        }
+    }
+    if !report.fixed.is_empty() {
+        println!();
+        println!("fixed since baseline (can be removed from it):");
+        for &(ref root_crate, ref key) in &report.fixed {
+            println!("- {} (in {})", key, root_crate);
+        }
+    }
+}
+
+// group findings by the crate defining the leaked item, sorted by crate name
+fn by_crate<'f>(findings: &[&'f Finding]) -> Vec<(&'f str, Vec<&'f Finding>)> {
+    let mut groups: Vec<(&str, Vec<&Finding>)> = Vec::new();
+    for &finding in findings {
+        let crate_name = finding.item.crate_name.as_str();
+        match groups.iter_mut().find(|&&mut (name, _)| name == crate_name) {
+            Some(&mut (_, ref mut group)) => group.push(finding),
+            None => groups.push((crate_name, vec![finding])),
+        }
+    }
+    groups.sort_by_key(|&(name, _)| name);
+    groups
+}
+
+// print the preferred fixes and whole-crate re-exports as snippets grouped by module
+fn output_human_fixes(findings: &[Finding]) {
+    for (kind, heading) in [
+        (FixKind::ReexportItem, "suggested fixes:"),
+        (FixKind::ReexportCrate, "alternatively, re-export whole crates:"),
+    ] {
+        let mut by_module: Vec<(&str, Vec<&str>)> = Vec::new();
+        for finding in findings {
+            let Some(fix) = finding.fixes.iter().find(|fix| fix.kind == kind) else { continue };
+            let i = by_module.iter().position(|&(module, _)| module == fix.module)
+                .unwrap_or_else(|| {
+                    by_module.push((&fix.module, Vec::new()));
+                    by_module.len() - 1
+                });
+            if !by_module[i].1.contains(&fix.statement.as_str()) {
+                by_module[i].1.push(&fix.statement);
+            }
+        }
+        if by_module.is_empty() {
+            continue;
+        }
+        by_module.sort();
+        println!();
+        println!("{}", heading);
+        for (module, statements) in by_module {
+            println!("// in {}", module);
+            for statement in statements {
+                println!("{}", statement);
+            }
+        }
+    }
+}
+
+fn output_json(report: &Report) -> Result<()> {
+    let findings = report.roots.iter()
+        .flat_map(|&(_, ref findings)| findings)
+        .map(finding_json)
+        .collect::<Result<Vec<Value>>>()?;
+    let fixed = report.fixed.iter()
+        .map(|&(ref root_crate, ref key)| json!({
+            "root_crate": root_crate,
+            "key": key,
+        }))
+        .collect::<Vec<_>>();
+    let document = json!({
+        "schema_version": JSON_SCHEMA_VERSION,
+        "findings": findings,
+        "fixed_baseline_entries": fixed,
+    });
+    println!("{}", serde_json::to_string_pretty(&document)?);
+    Ok(())
+}
+
+fn finding_json(finding: &Finding) -> Result<Value> {
+    Ok(json!({
+        "root_crate": finding.root_crate,
+        "item": {
+            "crate": finding.item.crate_name,
+            "kind": serde_json::to_value(finding.item.kind)?,
+            "path": finding.item.path,
+        },
+        "visible_path": finding.primary().visible_path,
+        "definition": finding.definition.as_ref().map(location_json),
+        "exposed_at": finding.primary().location.as_ref().map(location_json),
+        "sealed": finding.sealed,
+        "fixes": finding.fixes.iter()
+            .map(|fix| json!({
+                "kind": fix.kind.name(),
+                "module": fix.module,
+                "statement": fix.statement,
+            }))
+            .collect::<Vec<_>>(),
+        "witness": witness_json(&finding.primary().witness),
+        "exposures": finding.exposures.iter()
+            .map(|exposure| json!({
+                "visible_path": exposure.visible_path,
+                "exposed_at": exposure.location.as_ref().map(location_json),
+                "witness": witness_json(&exposure.witness),
+            }))
+            .collect::<Vec<_>>(),
+    }))
+}
+
+fn witness_json(witness: &[Hop]) -> Value {
+    witness.iter()
+        .map(|hop| json!({
+            "path": hop.path,
+            "edge": hop.edge.map(EdgeKind::describe),
+        }))
+        .collect()
+}
+
+fn location_json(location: &Location) -> Value {
+    json!({
+        "file": location.file,
+        "line": location.line,
+        "column": location.column,
+        "end_line": location.end_line,
+        "end_column": location.end_column,
+    })
+}
+
+fn output_diagnostics(report: &Report) {
+    let mut sources = SourceCache::new(&report.workspace_root);
+    let mut count = 0;
+    for &(_, ref findings) in &report.roots {
+        for finding in findings {
+            let item_path = finding.item_path();
+            if finding.sealed {
+                println!("note: sealed trait `{}` is not importable", item_path);
+            } else {
+                count += 1;
+                println!("warning: `{}` is visible but not importable", item_path);
+            }
+            let primary = finding.primary();
+            let location = primary.location.as_ref().or(finding.definition.as_ref());
+            let gutter = finding.exposures.iter()
+                .filter_map(|exposure| exposure.location.as_ref())
+                .chain(finding.definition.as_ref())
+                .map(|location| location.line.to_string().len())
+                .max()
+                .unwrap_or(0);
+            if let Some(location) = location {
+                print_snippet(&mut sources, location, gutter);
+            }
+            println!("{:gutter$} = note: visible as `{}`", "", primary.visible_path);
+            if let Some(fix) = finding.fixes.first() {
+                println!("{:gutter$} = help: add `{}` to `{}`", "", fix.statement, fix.module);
+            }
+            for exposure in &finding.exposures[1..] {
+                println!("note: also visible as `{}`", exposure.visible_path);
+                if let Some(ref location) = exposure.location {
+                    print_snippet(&mut sources, location, gutter);
+                }
+            }
+            if primary.location.is_some() {
+                if let Some(ref definition) = finding.definition {
+                    println!("note: `{}` is defined here", item_path);
+                    print_snippet(&mut sources, definition, gutter);
+                }
+            }
+            println!();
+        }
+    }
+    if count > 0 {
+        println!("warning: {} item(s) visible but not importable", count);
+    }
+}
+
+// print a location's arrow line and its first line of source, underlined, rustc-style
+fn print_snippet(sources: &mut SourceCache, location: &Location, gutter: usize) {
+    println!("{:gutter$}--> {}", "", location);
+    let Some(line) = sources.line(&location.file, location.line) else { return };
+    let start = location.column.saturating_sub(1);
+    let end = if location.end_line == location.line {
+        location.end_column.saturating_sub(1)
+    } else {
+        line.chars().count()
+    };
+    println!("{:gutter$} |", "");
+    println!("{:>gutter$} | {}", location.line, line);
+    println!("{:gutter$} | {}{}", "", " ".repeat(start), "^".repeat(end.saturating_sub(start).max(1)));
+}
+
+// lazily read source files, which are only needed for locations being printed
+struct SourceCache<'a> {
+    workspace_root: &'a Path,
+    files: HashMap<PathBuf, Option<String>>,
+}
+
+impl<'a> SourceCache<'a> {
+    fn new(workspace_root: &'a Path) -> Self {
+        SourceCache { workspace_root, files: HashMap::new() }
+    }
+
+    // get the given 1-indexed line of a file, if it can be read
+    fn line(&mut self, file: &Path, line: usize) -> Option<&str> {
+        let workspace_root = self.workspace_root;
+        self.files.entry(file.to_owned())
+            .or_insert_with(|| fs::read_to_string(workspace_root.join(file)).ok())
+            .as_ref()?
+            .lines()
+            .nth(line.checked_sub(1)?)
+    }
+}
+
+fn output_github(report: &Report) {
+    for &(_, ref findings) in &report.roots {
+        for finding in findings {
+            let command = if finding.sealed { "notice" } else { "warning" };
+            let item_path = finding.item_path();
+            // annotations can only be shown on files in the repository, which absolute paths of
+            // dependencies aren't. each exposing signature in the repository is annotated, and if
+            // there are none the finding is annotated once, on the item's definition if possible.
+            let mut annotations = finding.exposures.iter()
+                .filter(|exposure| exposure.location.as_ref()
+                    .is_some_and(|location| location.file.is_relative()))
+                .map(|exposure| (exposure, exposure.location.as_ref()))
+                .collect::<Vec<_>>();
+            if annotations.is_empty() {
+                let definition = finding.definition.as_ref()
+                    .filter(|location| location.file.is_relative());
+                annotations.push((finding.primary(), definition));
+            }
+            for (exposure, location) in annotations {
+                let exposed_by = exposure.exposed_by()
+                    .map(|hop| hop.path.as_str())
+                    .unwrap_or(&finding.root_crate);
+                let mut message = format!(
+                    "`{}` is exposed by `{}` but not importable", item_path, exposed_by,
+                );
+                if let Some(fix) = finding.fixes.first() {
+                    message.push_str(
+                        &format!("\nConsider adding `{}` to `{}`", fix.statement, fix.module),
+                    );
+                }
+                let mut properties = Vec::new();
+                if let Some(location) = location {
+                    let file = report.repository_path(location);
+                    properties.push(("file", file.to_string_lossy().into_owned()));
+                    properties.push(("line", location.line.to_string()));
+                    properties.push(("col", location.column.to_string()));
+                    properties.push(("endLine", location.end_line.to_string()));
+                    properties.push(("endColumn", location.end_column.to_string()));
+                }
+                properties.push(("title", finding.category().id().to_owned()));
+                let properties = properties.iter()
+                    .map(|&(key, ref value)| format!("{}={}", key, github_escape(value, true)))
+                    .collect::<Vec<_>>()
+                    .join(",");
+                println!("::{} {}::{}", command, properties, github_escape(&message, false));
+            }
+        }
+    }
+}
+
+// escape data or a property value of a workflow command
+fn github_escape(s: &str, property: bool) -> String {
+    let mut escaped = String::new();
+    for c in s.chars() {
+        match c {
+            '%' => escaped.push_str("%25"),
+            '\r' => escaped.push_str("%0D"),
+            '\n' => escaped.push_str("%0A"),
+            ':' if property => escaped.push_str("%3A"),
+            ',' if property => escaped.push_str("%2C"),
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+fn output_markdown(report: &Report) -> Result<()> {
+    // nest crate headings under root crate headings if there are multiple root crates
+    let heading = if report.grouped { "###" } else { "##" };
+    for (i, &(ref root_package, ref findings)) in report.roots.iter().enumerate() {
+        if i > 0 {
+            println!();
+        }
+        if report.grouped {
+            println!("## `{}`", root_package);
+            println!();
+        }
+        let leaks = findings.iter().filter(|finding| !finding.sealed).collect::<Vec<_>>();
+        if leaks.is_empty() {
+            println!("No items are visible but not importable.");
+        }
+        for (j, (crate_name, leaks)) in by_crate(&leaks).into_iter().enumerate() {
+            if j > 0 {
+                println!();
+            }
+            println!("{} Defined in `{}`", heading, crate_name);
+            println!();
+            println!("| Leaked item | Kind | Exposed by | Suggested fix |");
+            println!("| --- | --- | --- | --- |");
+            for finding in leaks {
+                let item_path = finding.item_path();
+                let exposed_by = finding.exposures.iter()
+                    .map(|exposure| match exposure.exposed_by() {
+                        Some(hop) => format!(
+                            "{} ({})",
+                            markdown_code(&hop.path),
+                            exposure.witness.last().and_then(|hop| hop.edge)
+                                .map(EdgeKind::describe)
+                                .unwrap_or("crate root"),
+                        ),
+                        None => markdown_code(&exposure.visible_path),
+                    })
+                    .collect::<Vec<_>>()
+                    .join("<br>");
+                let fix = finding.fixes.first()
+                    .map(|fix| format!(
+                        "{} in {}", markdown_code(&fix.statement), markdown_code(&fix.module),
+                    ))
+                    .unwrap_or_default();
+                println!(
+                    "| {} | {} | {} | {} |",
+                    markdown_code(&item_path),
+                    serde_json::to_value(finding.item.kind)?.as_str().unwrap_or_default(),
+                    exposed_by,
+                    fix,
+                );
+            }
+        }
+        let sealed = findings.iter().filter(|finding| finding.sealed).collect::<Vec<_>>();
+        if !sealed.is_empty() {
+            println!();
+            println!("{} Sealed traits", heading);
+            println!();
+            for finding in sealed {
+                println!("- {}", markdown_code(&finding.primary().visible_path));
+            }
+        }
+    }
+    if !report.fixed.is_empty() {
+        println!();
+        println!("## Fixed since baseline");
+        println!();
+        for &(ref root_crate, ref key) in &report.fixed {
+            println!("- {} (in {})", markdown_code(key), markdown_code(root_crate));
+        }
+    }
+    Ok(())
+}
+
+// format as inline code which is safe to put in a table cell
+fn markdown_code(s: &str) -> String {
+    format!("`{}`", s.replace('`', "'").replace('|', "\\|"))
+}
@@ -0,0 +1,163 @@
+//! Outputting findings as a SARIF 2.1.0 log.
+
+use crate::{
+    error::*,
+    baseline::finding_key,
+    report::{Report, Finding, Location, Category},
+};
+use std::path::{Path, Component};
+use serde_json::{Value, json};
+
+
+const SARIF_SCHEMA: &'static str = "https://json.schemastore.org/sarif-2.1.0.json";
+const INFORMATION_URI: &'static str =
+    "https://github.com/gretchenfrage/cargo-should-be-public-checker";
+// uriBaseId of locations relative to the repository root
+const SRCROOT: &'static str = "%SRCROOT%";
+
+/// Output the report as a SARIF log with a single run.
+pub fn output_sarif(report: &Report) -> Result<()> {
+    let rules = Category::ALL.iter()
+        .map(|&category| json!({
+            "id": category.id(),
+            "shortDescription": { "text": category.description() },
+            "defaultConfiguration": { "level": level(category) },
+        }))
+        .collect::<Vec<_>>();
+    let results = report.roots.iter()
+        .flat_map(|&(_, ref findings)| findings)
+        .map(|finding| result_json(report, finding))
+        .collect::<Vec<_>>();
+    let document = json!({
+        "$schema": SARIF_SCHEMA,
+        "version": "2.1.0",
+        "runs": [{
+            "tool": {
+                "driver": {
+                    "name": env!("CARGO_PKG_NAME"),
+                    "informationUri": INFORMATION_URI,
+                    "rules": rules,
+                },
+            },
+            "originalUriBaseIds": {
+                SRCROOT: { "uri": directory_uri(&report.repository_root) },
+            },
+            "results": results,
+        }],
+    });
+    println!("{}", serde_json::to_string_pretty(&document)?);
+    Ok(())
+}
+
+fn level(category: Category) -> &'static str {
+    match category {
+        Category::SealedTrait => "note",
+        _ => "warning",
+    }
+}
+
+fn result_json(report: &Report, finding: &Finding) -> Value {
+    let category = finding.category();
+    let key = finding_key(finding);
+    let primary = finding.primary();
+    let mut message = format!("`{}` is visible as `{}` but not importable", key, primary.visible_path);
+    if let Some(fix) = finding.fixes.first() {
+        message.push_str(&format!(". Consider adding `{}` to `{}`.", fix.statement, fix.module));
+    }
+    // the leaking signature is the primary location, so that results show up where the API is
+    // being defined
+    let location = primary.location.as_ref().or(finding.definition.as_ref());
+    let definition = finding.definition.as_ref()
+        .filter(|_| primary.location.is_some())
+        .map(|definition| (definition, format!("`{}` is defined here", key)));
+    let other_exposures = finding.exposures[1..].iter()
+        .filter_map(|exposure| exposure.location.as_ref()
+            .map(|location| (location, format!("also visible as `{}`", exposure.visible_path))));
+    let related = definition.into_iter()
+        .chain(other_exposures)
+        .enumerate()
+        .map(|(i, (location, message))| json!({
+            "id": i,
+            "physicalLocation": physical_location(report, location),
+            "message": { "text": message },
+        }))
+        .collect::<Vec<_>>();
+    json!({
+        "ruleId": category.id(),
+        "ruleIndex": Category::ALL.iter().position(|&c| c == category),
+        "level": level(category),
+        "message": { "text": message },
+        "locations": location.into_iter()
+            .map(|location| json!({ "physicalLocation": physical_location(report, location) }))
+            .collect::<Vec<_>>(),
+        "relatedLocations": related,
+        // keyed on the canonical path, like baselines, so that they're stable across runs
+        "partialFingerprints": {
+            "canonicalItemPath/v1": format!("{}:{}", finding.root_crate, key),
+        },
+    })
+}
+
+fn physical_location(report: &Report, location: &Location) -> Value {
+    let file = report.repository_path(location);
+    let artifact_location = if file.is_absolute() {
+        json!({ "uri": file_uri(&file) })
+    } else {
+        json!({ "uri": relative_uri(&file), "uriBaseId": SRCROOT })
+    };
+    json!({
+        "artifactLocation": artifact_location,
+        "region": {
+            "startLine": location.line,
+            "startColumn": location.column,
+            "endLine": location.end_line,
+            "endColumn": location.end_column,
+        },
+    })
+}
+
+// SARIF requires URIs rather than paths, and base URIs to end with a slash
+fn directory_uri(path: &Path) -> String {
+    let mut uri = file_uri(path);
+    if !uri.ends_with('/') {
+        uri.push('/');
+    }
+    uri
+}
+
+fn file_uri(path: &Path) -> String {
+    let uri = relative_uri(path);
+    if uri.starts_with('/') {
+        format!("file://{}", uri)
+    } else {
+        // windows paths start with a drive letter
+        format!("file:///{}", uri)
+    }
+}
+
+fn relative_uri(path: &Path) -> String {
+    let mut uri = String::new();
+    for component in path.components() {
+        match component {
+            Component::Prefix(prefix) => uri.push_str(&prefix.as_os_str().to_string_lossy()),
+            Component::RootDir => uri.push('/'),
+            component => {
+                if !uri.is_empty() && !uri.ends_with('/') {
+                    uri.push('/');
+                }
+                for c in component.as_os_str().to_string_lossy().chars() {
+                    match c {
+                        'A'..='Z' | 'a'..='z' | '0'..='9' | '-' | '.' | '_' | '~' => uri.push(c),
+                        _ => {
+                            let mut buf = [0; 4];
+                            for byte in c.encode_utf8(&mut buf).bytes() {
+                                uri.push_str(&format!("%{:02X}", byte));
+                            }
+                        }
+                    }
+                }
+            }
+        }
+    }
+    uri
+}
@@ -0,0 +1,69 @@
+//! Runs the checker on every fixture and compares its output to the fixture's `expected.txt`, and
+//! for each `<name>.args` file in the fixture, runs it again with the arguments listed in that file,
+//! one per line, and compares its output to `<name>.txt`.
+//!
+//! The fixtures are built with the nightly pinned in `fixtures/rust-toolchain.toml`, which must be
+//! installed.
+
+use std::{
+    fs,
+    path::Path,
+    process::Command,
+};
+
+
+#[test]
+fn fixtures() {
+    let fixtures_dir = Path::new(env!("CARGO_MANIFEST_DIR")).join("fixtures");
+    let mut fixtures = fs::read_dir(&fixtures_dir).unwrap()
+        .map(|entry| entry.unwrap().path())
+        .filter(|path| path.join("expected.txt").is_file())
+        .collect::<Vec<_>>();
+    fixtures.sort();
+    assert!(!fixtures.is_empty(), "No fixtures in {}", fixtures_dir.display());
+
+    let mut failures = Vec::new();
+    for fixture in &fixtures {
+        // (arguments, file with the expected output)
+        let mut runs = vec![(Vec::new(), fixture.join("expected.txt"))];
+        let mut args_files = fs::read_dir(fixture).unwrap()
+            .map(|entry| entry.unwrap().path())
+            .filter(|path| path.extension().is_some_and(|extension| extension == "args"))
+            .collect::<Vec<_>>();
+        args_files.sort();
+        for args_file in args_files {
+            let args = fs::read_to_string(&args_file).unwrap()
+                .lines()
+                .filter(|line| !line.is_empty())
+                .map(String::from)
+                .collect();
+            runs.push((args, args_file.with_extension("txt")));
+        }
+
+        for (args, expected_file) in runs {
+            let output = Command::new(env!("CARGO_BIN_EXE_should-be-public"))
+                .arg(fixture)
+                .arg("--no-cache")
+                .args(&args)
+                // set by rustup for `cargo test`, and would take precedence over the toolchain file
+                // if it names a nightly
+                .env_remove("RUSTUP_TOOLCHAIN")
+                .output()
+                .unwrap();
+            let expected = fs::read_to_string(&expected_file).unwrap();
+            let actual = String::from_utf8_lossy(&output.stdout);
+            if !output.status.success() {
+                failures.push(format!(
+                    "{} {:?} failed:\n{}",
+                    fixture.display(), args, String::from_utf8_lossy(&output.stderr),
+                ));
+            } else if actual != expected {
+                failures.push(format!(
+                    "{} {:?} output differs from {}:\n{}",
+                    fixture.display(), args, expected_file.display(), actual,
+                ));
+            }
+        }
+    }
+    assert!(failures.is_empty(), "{}", failures.join("\n"));
+}